@@ -0,0 +1,472 @@
+use chain::{Chain, GAP};
+
+/// Residue order of the protein substitution matrices
+const AMINO_ACIDS: &str = "ARNDCQEGHILKMFPSTWYVBZX*";
+
+#[rustfmt::skip]
+const BLOSUM62: [[i8; 24]; 24] = [
+    [ 4, -1, -2, -2,  0, -1, -1,  0, -2, -1, -1, -1, -1, -2, -1,  1,  0, -3, -2,  0, -2, -1,  0, -4],
+    [-1,  5,  0, -2, -3,  1,  0, -2,  0, -3, -2,  2, -1, -3, -2, -1, -1, -3, -2, -3, -1,  0, -1, -4],
+    [-2,  0,  6,  1, -3,  0,  0,  0,  1, -3, -3,  0, -2, -3, -2,  1,  0, -4, -2, -3,  3,  0, -1, -4],
+    [-2, -2,  1,  6, -3,  0,  2, -1, -1, -3, -4, -1, -3, -3, -1,  0, -1, -4, -3, -3,  4,  1, -1, -4],
+    [ 0, -3, -3, -3,  9, -3, -4, -3, -3, -1, -1, -3, -1, -2, -3, -1, -1, -2, -2, -1, -3, -3, -2, -4],
+    [-1,  1,  0,  0, -3,  5,  2, -2,  0, -3, -2,  1,  0, -3, -1,  0, -1, -2, -1, -2,  0,  3, -1, -4],
+    [-1,  0,  0,  2, -4,  2,  5, -2,  0, -3, -3,  1, -2, -3, -1,  0, -1, -3, -2, -2,  1,  4, -1, -4],
+    [ 0, -2,  0, -1, -3, -2, -2,  6, -2, -4, -4, -2, -3, -3, -2,  0, -2, -2, -3, -3, -1, -2, -1, -4],
+    [-2,  0,  1, -1, -3,  0,  0, -2,  8, -3, -3, -1, -2, -1, -2, -1, -2, -2,  2, -3,  0,  0, -1, -4],
+    [-1, -3, -3, -3, -1, -3, -3, -4, -3,  4,  2, -3,  1,  0, -3, -2, -1, -3, -1,  3, -3, -3, -1, -4],
+    [-1, -2, -3, -4, -1, -2, -3, -4, -3,  2,  4, -2,  2,  0, -3, -2, -1, -2, -1,  1, -4, -3, -1, -4],
+    [-1,  2,  0, -1, -3,  1,  1, -2, -1, -3, -2,  5, -1, -3, -1,  0, -1, -3, -2, -2,  0,  1, -1, -4],
+    [-1, -1, -2, -3, -1,  0, -2, -3, -2,  1,  2, -1,  5,  0, -2, -1, -1, -1, -1,  1, -3, -1, -1, -4],
+    [-2, -3, -3, -3, -2, -3, -3, -3, -1,  0,  0, -3,  0,  6, -4, -2, -2,  1,  3, -1, -3, -3, -1, -4],
+    [-1, -2, -2, -1, -3, -1, -1, -2, -2, -3, -3, -1, -2, -4,  7, -1, -1, -4, -3, -2, -2, -1, -2, -4],
+    [ 1, -1,  1,  0, -1,  0,  0,  0, -1, -2, -2,  0, -1, -2, -1,  4,  1, -3, -2, -2,  0,  0,  0, -4],
+    [ 0, -1,  0, -1, -1, -1, -1, -2, -2, -1, -1, -1, -1, -2, -1,  1,  5, -2, -2,  0, -1, -1,  0, -4],
+    [-3, -3, -4, -4, -2, -2, -3, -2, -2, -3, -2, -3, -1,  1, -4, -3, -2, 11,  2, -3, -4, -3, -2, -4],
+    [-2, -2, -2, -3, -2, -1, -2, -3,  2, -1, -1, -2, -1,  3, -3, -2, -2,  2,  7, -1, -3, -2, -1, -4],
+    [ 0, -3, -3, -3, -1, -2, -2, -3, -3,  3,  1, -2,  1, -1, -2, -2,  0, -3, -1,  4, -3, -2, -1, -4],
+    [-2, -1,  3,  4, -3,  0,  1, -1,  0, -3, -4,  0, -3, -3, -2,  0, -1, -4, -3, -3,  4,  1, -1, -4],
+    [-1,  0,  0,  1, -3,  3,  4, -2,  0, -3, -3,  1, -1, -3, -1,  0, -1, -3, -2, -2,  1,  4, -1, -4],
+    [ 0, -1, -1, -1, -2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -2,  0,  0, -2, -1, -1, -1, -1, -1, -4],
+    [-4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4,  1],
+];
+
+#[rustfmt::skip]
+const PAM250: [[i8; 24]; 24] = [
+    [ 2, -2,  0,  0, -2,  0,  0,  1, -1, -1, -2, -1, -1, -3,  1,  1,  1, -6, -3,  0,  0,  0,  0, -8],
+    [-2,  6,  0, -1, -4,  1, -1, -3,  2, -2, -3,  3,  0, -4,  0,  0, -1,  2, -4, -2, -1,  0, -1, -8],
+    [ 0,  0,  2,  2, -4,  1,  1,  0,  2, -2, -3,  1, -2, -3,  0,  1,  0, -4, -2, -2,  2,  1,  0, -8],
+    [ 0, -1,  2,  4, -5,  2,  3,  1,  1, -2, -4,  0, -3, -6, -1,  0,  0, -7, -4, -2,  3,  3, -1, -8],
+    [-2, -4, -4, -5, 12, -5, -5, -3, -3, -2, -6, -5, -5, -4, -3,  0, -2, -8,  0, -2, -4, -5, -3, -8],
+    [ 0,  1,  1,  2, -5,  4,  2, -1,  3, -2, -2,  1, -1, -5,  0, -1, -1, -5, -4, -2,  1,  3, -1, -8],
+    [ 0, -1,  1,  3, -5,  2,  4,  0,  1, -2, -3,  0, -2, -5, -1,  0,  0, -7, -4, -2,  3,  3, -1, -8],
+    [ 1, -3,  0,  1, -3, -1,  0,  5, -2, -3, -4, -2, -3, -5,  0,  1,  0, -7, -5, -1,  0,  0, -1, -8],
+    [-1,  2,  2,  1, -3,  3,  1, -2,  6, -2, -2,  0, -2, -2,  0, -1, -1, -3,  0, -2,  1,  2, -1, -8],
+    [-1, -2, -2, -2, -2, -2, -2, -3, -2,  5,  2, -2,  2,  1, -2, -1,  0, -5, -1,  4, -2, -2, -1, -8],
+    [-2, -3, -3, -4, -6, -2, -3, -4, -2,  2,  6, -3,  4,  2, -3, -3, -2, -2, -1,  2, -3, -3, -1, -8],
+    [-1,  3,  1,  0, -5,  1,  0, -2,  0, -2, -3,  5,  0, -5, -1,  0,  0, -3, -4, -2,  1,  0, -1, -8],
+    [-1,  0, -2, -3, -5, -1, -2, -3, -2,  2,  4,  0,  6,  0, -2, -2, -1, -4, -2,  2, -2, -2, -1, -8],
+    [-3, -4, -3, -6, -4, -5, -5, -5, -2,  1,  2, -5,  0,  9, -5, -3, -3,  0,  7, -1, -4, -5, -2, -8],
+    [ 1,  0,  0, -1, -3,  0, -1,  0,  0, -2, -3, -1, -2, -5,  6,  1,  0, -6, -5, -1, -1,  0, -1, -8],
+    [ 1,  0,  1,  0,  0, -1,  0,  1, -1, -1, -3,  0, -2, -3,  1,  2,  1, -2, -3, -1,  0,  0,  0, -8],
+    [ 1, -1,  0,  0, -2, -1,  0,  0, -1,  0, -2,  0, -1, -3,  0,  1,  3, -5, -3,  0,  0, -1,  0, -8],
+    [-6,  2, -4, -7, -8, -5, -7, -7, -3, -5, -2, -3, -4,  0, -6, -2, -5, 17,  0, -6, -5, -6, -4, -8],
+    [-3, -4, -2, -4,  0, -4, -4, -5,  0, -1, -1, -4, -2,  7, -5, -3, -3,  0, 10, -2, -3, -4, -2, -8],
+    [ 0, -2, -2, -2, -2, -2, -2, -1, -2,  4,  2, -2,  2, -1, -1, -1,  0, -6, -2,  4, -2, -2, -1, -8],
+    [ 0, -1,  2,  3, -4,  1,  3,  0,  1, -2, -3,  1, -2, -4, -1,  0,  0, -5, -3, -2,  3,  2, -1, -8],
+    [ 0,  0,  1,  3, -5,  3,  3,  0,  2, -2, -3,  0, -2, -5,  0,  0, -1, -6, -4, -2,  2,  3, -1, -8],
+    [ 0, -1,  0, -1, -3, -1, -1, -1, -1, -1, -1, -1, -1, -2, -1,  0,  0, -4, -2, -1, -1, -1, -1, -8],
+    [-8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8, -8,  1],
+];
+
+/// Substitution scores of residue pairs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SubstitutionMatrix {
+    /// BLOSUM62 for protein sequences
+    #[default]
+    Blosum62,
+
+    /// PAM250 for distantly related protein sequences
+    Pam250,
+
+    /// Nucleotide scores: 5 for a match, -4 for a mismatch and -2 with an
+    /// unknown nucleotide. `U` is treated as `T`.
+    Nucleotide,
+}
+
+/// Index of a residue code in [`AMINO_ACIDS`](constant.AMINO_ACIDS.html), unknown codes are `X`
+fn amino_acid_index(code: char) -> usize {
+    let code = code.to_ascii_uppercase();
+    AMINO_ACIDS.chars().position(|c| c == code).unwrap_or(22)
+}
+
+impl SubstitutionMatrix {
+    /// Score of aligning the residue codes `a` and `b`, case-insensitive
+    pub fn score(&self, a: char, b: char) -> i32 {
+        match *self {
+            SubstitutionMatrix::Blosum62 => {
+                i32::from(BLOSUM62[amino_acid_index(a)][amino_acid_index(b)])
+            }
+            SubstitutionMatrix::Pam250 => {
+                i32::from(PAM250[amino_acid_index(a)][amino_acid_index(b)])
+            }
+            SubstitutionMatrix::Nucleotide => {
+                let base = |code: char| match code.to_ascii_uppercase() {
+                    'U' => Some('T'),
+                    code @ 'A' | code @ 'C' | code @ 'G' | code @ 'T' => Some(code),
+                    _ => None,
+                };
+                match (base(a), base(b)) {
+                    (Some(a), Some(b)) if a == b => 5,
+                    (Some(_), Some(_)) => -4,
+                    _ => -2,
+                }
+            }
+        }
+    }
+}
+
+/// Alignment algorithm
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AlignmentMode {
+    /// Global alignment of the full sequences (Needleman–Wunsch)
+    #[default]
+    Global,
+
+    /// Best scoring local alignment of subsequences (Smith–Waterman)
+    Local,
+}
+
+/// Options of the pairwise sequence alignment
+///
+/// Gaps are scored with affine penalties: a gap of length `k` costs
+/// `gap_open + (k - 1) * gap_extend`.
+#[derive(Debug, Clone)]
+pub struct AlignmentOptions {
+    /// Substitution scores of aligned residues
+    pub matrix: SubstitutionMatrix,
+
+    /// Global or local alignment
+    pub mode: AlignmentMode,
+
+    /// Penalty of the first position of a gap
+    pub gap_open: i32,
+
+    /// Penalty of each further position of a gap
+    pub gap_extend: i32,
+}
+
+impl Default for AlignmentOptions {
+    fn default() -> Self {
+        AlignmentOptions {
+            matrix: SubstitutionMatrix::Blosum62,
+            mode: AlignmentMode::Global,
+            gap_open: 10,
+            gap_extend: 1,
+        }
+    }
+}
+
+/// Result of a pairwise sequence alignment
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alignment {
+    /// Alignment score
+    pub score: i32,
+
+    /// Aligned positions of both sequences, `None` marks a gap
+    pub pairs: Vec<(Option<usize>, Option<usize>)>,
+}
+
+impl Alignment {
+    /// Pairs of positions aligned to each other, without gaps
+    pub fn correspondences(&self) -> Vec<(usize, usize)> {
+        self.pairs
+            .iter()
+            .filter_map(|pair| match *pair {
+                (Some(a), Some(b)) => Some((a, b)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Fraction of aligned pairs with identical residue codes
+    pub fn identity(&self, a: &str, b: &str) -> f32 {
+        let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+        let pairs = self.correspondences();
+        if pairs.is_empty() {
+            return 0.0;
+        }
+        let identical = pairs
+            .iter()
+            .filter(|&&(i, j)| a[i].eq_ignore_ascii_case(&b[j]))
+            .count();
+        identical as f32 / pairs.len() as f32
+    }
+
+    /// Both sequences with gaps inserted, as aligned
+    pub fn aligned_strings(&self, a: &str, b: &str) -> (String, String) {
+        let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+        self.pairs
+            .iter()
+            .map(|&(i, j)| (i.map_or(GAP, |i| a[i]), j.map_or(GAP, |j| b[j])))
+            .unzip()
+    }
+}
+
+/// Lowest score, low enough to never be chosen but safe to subtract from
+const NEG: i32 = i32::MIN / 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Match,
+    GapB,
+    GapA,
+}
+
+/// Align the sequences `a` and `b` with affine gap penalties (Gotoh)
+///
+/// # Examples
+///
+/// ```
+/// use mmtf::alignment::{align, AlignmentOptions};
+///
+/// let alignment = align("ACDEFGHIKL", "ACDKL", &AlignmentOptions::default());
+/// let (a, b) = alignment.aligned_strings("ACDEFGHIKL", "ACDKL");
+///
+/// assert_eq!("ACDEFGHIKL", a);
+/// assert_eq!("ACD-----KL", b);
+/// assert_eq!(vec![(0, 0), (1, 1), (2, 2), (8, 3), (9, 4)], alignment.correspondences());
+/// ```
+pub fn align(a: &str, b: &str, options: &AlignmentOptions) -> Alignment {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let (n, m) = (a.len(), b.len());
+    let local = options.mode == AlignmentMode::Local;
+    let (open, extend) = (options.gap_open, options.gap_extend);
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+
+    // best scores ending with a pair, with a gap in `b` and with a gap in `a`
+    let mut pair = vec![NEG; (n + 1) * (m + 1)];
+    let mut gap_b = vec![NEG; (n + 1) * (m + 1)];
+    let mut gap_a = vec![NEG; (n + 1) * (m + 1)];
+    pair[0] = 0;
+
+    for i in 0..=n {
+        for j in 0..=m {
+            if i > 0 && j > 0 {
+                let previous = at(i - 1, j - 1);
+                let best = pair[previous].max(gap_b[previous]).max(gap_a[previous]);
+                let best = if local { best.max(0) } else { best };
+                pair[at(i, j)] = best + options.matrix.score(a[i - 1], b[j - 1]);
+            }
+            if i > 0 {
+                let previous = at(i - 1, j);
+                gap_b[at(i, j)] = (pair[previous] - open)
+                    .max(gap_b[previous] - extend)
+                    .max(gap_a[previous] - open);
+            }
+            if j > 0 {
+                let previous = at(i, j - 1);
+                gap_a[at(i, j)] = (pair[previous] - open)
+                    .max(gap_a[previous] - extend)
+                    .max(gap_b[previous] - open);
+            }
+        }
+    }
+
+    let best_state = |index: usize| {
+        if pair[index] >= gap_b[index] && pair[index] >= gap_a[index] {
+            State::Match
+        } else if gap_b[index] >= gap_a[index] {
+            State::GapB
+        } else {
+            State::GapA
+        }
+    };
+
+    let (mut i, mut j, mut state) = if local {
+        let mut end = (0, 0);
+        for i in 1..=n {
+            for j in 1..=m {
+                if pair[at(i, j)] > pair[at(end.0, end.1)] {
+                    end = (i, j);
+                }
+            }
+        }
+        (end.0, end.1, State::Match)
+    } else {
+        (n, m, best_state(at(n, m)))
+    };
+    let score = match state {
+        State::Match => pair[at(i, j)],
+        State::GapB => gap_b[at(i, j)],
+        State::GapA => gap_a[at(i, j)],
+    };
+    if local && score <= 0 {
+        return Alignment {
+            score: 0,
+            pairs: Vec::new(),
+        };
+    }
+
+    let mut pairs = Vec::new();
+    while i > 0 || j > 0 {
+        match state {
+            State::Match => {
+                pairs.push((Some(i - 1), Some(j - 1)));
+                let start = pair[at(i, j)] - options.matrix.score(a[i - 1], b[j - 1]);
+                i -= 1;
+                j -= 1;
+                if local && start == 0 {
+                    break;
+                }
+                let previous = at(i, j);
+                state = if pair[previous] == start {
+                    State::Match
+                } else if gap_b[previous] == start {
+                    State::GapB
+                } else {
+                    State::GapA
+                };
+            }
+            State::GapB => {
+                pairs.push((Some(i - 1), None));
+                let value = gap_b[at(i, j)];
+                i -= 1;
+                let previous = at(i, j);
+                state = if gap_b[previous] - extend == value {
+                    State::GapB
+                } else if pair[previous] - open == value {
+                    State::Match
+                } else {
+                    State::GapA
+                };
+            }
+            State::GapA => {
+                pairs.push((None, Some(j - 1)));
+                let value = gap_a[at(i, j)];
+                j -= 1;
+                let previous = at(i, j);
+                state = if gap_a[previous] - extend == value {
+                    State::GapA
+                } else if pair[previous] - open == value {
+                    State::Match
+                } else {
+                    State::GapB
+                };
+            }
+        }
+    }
+    pairs.reverse();
+    Alignment { score, pairs }
+}
+
+impl<'a> Chain<'a> {
+    /// Align the observed residues of two chains and return the corresponding
+    /// group indices, see [`Chain::residue_codes`](../chain/struct.Chain.html#method.residue_codes)
+    pub fn align_observed(&self, other: &Chain, options: &AlignmentOptions) -> Vec<(usize, usize)> {
+        let (residues, other_residues) = (self.residue_codes(), other.residue_codes());
+        let sequence: String = residues.iter().map(|&(_, code)| code).collect();
+        let other_sequence: String = other_residues.iter().map(|&(_, code)| code).collect();
+
+        align(&sequence, &other_sequence, options)
+            .correspondences()
+            .into_iter()
+            .map(|(i, j)| (residues[i].0, other_residues[j].0))
+            .collect()
+    }
+
+    /// Align the entity sequences of two chains and return the corresponding
+    /// group indices of positions observed in both chains
+    pub fn align_entity(&self, other: &Chain, options: &AlignmentOptions) -> Vec<(usize, usize)> {
+        let (sequence, other_sequence) = match (self.sequence(), other.sequence()) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return Vec::new(),
+        };
+        let (groups, other_groups) = (self.sequence_groups(), other.sequence_groups());
+
+        align(sequence, other_sequence, options)
+            .correspondences()
+            .into_iter()
+            .filter_map(|(i, j)| match (groups[i], other_groups[j]) {
+                (Some(a), Some(b)) => Some((a, b)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    #[test]
+    fn test_symmetric_matrices() {
+        for i in 0..24 {
+            for j in 0..24 {
+                assert_eq!(BLOSUM62[i][j], BLOSUM62[j][i]);
+                assert_eq!(PAM250[i][j], PAM250[j][i]);
+            }
+        }
+        let blosum = SubstitutionMatrix::Blosum62;
+        assert_eq!(11, blosum.score('W', 'w'));
+        assert_eq!(-1, blosum.score('Z', 'O'));
+        assert_eq!(17, SubstitutionMatrix::Pam250.score('W', 'W'));
+        assert_eq!(5, SubstitutionMatrix::Nucleotide.score('U', 't'));
+        assert_eq!(-4, SubstitutionMatrix::Nucleotide.score('A', 'G'));
+    }
+
+    #[test]
+    fn it_align_globally() {
+        let options = AlignmentOptions {
+            gap_open: 8,
+            gap_extend: 8,
+            ..Default::default()
+        };
+        let alignment = align("HEAGAWGHEE", "PAWHEAE", &options);
+        let (a, b) = alignment.aligned_strings("HEAGAWGHEE", "PAWHEAE");
+
+        assert_eq!(a.len(), b.len());
+        assert_eq!("HEAGAWGHEE", a.replace('-', ""));
+        assert_eq!("PAWHEAE", b.replace('-', ""));
+
+        let score: i32 = alignment
+            .pairs
+            .iter()
+            .map(|pair| match *pair {
+                (Some(i), Some(j)) => options.matrix.score(
+                    "HEAGAWGHEE".as_bytes()[i] as char,
+                    "PAWHEAE".as_bytes()[j] as char,
+                ),
+                _ => -8,
+            })
+            .sum();
+        assert_eq!(score, alignment.score);
+    }
+
+    #[test]
+    fn it_align_locally() {
+        let options = AlignmentOptions {
+            mode: AlignmentMode::Local,
+            gap_open: 8,
+            gap_extend: 8,
+            ..Default::default()
+        };
+        let alignment = align("HEAGAWGHEE", "PAWHEAE", &options);
+        let (a, b) = alignment.aligned_strings("HEAGAWGHEE", "PAWHEAE");
+
+        assert_eq!(("AWGHE".to_string(), "AW-HE".to_string()), (a, b));
+        assert_eq!(20, alignment.score);
+        assert_eq!(
+            vec![(4, 1), (5, 2), (7, 3), (8, 4)],
+            alignment.correspondences()
+        );
+
+        assert!(align("WWW", "PPP", &options).pairs.is_empty());
+    }
+
+    #[test]
+    fn it_prefer_single_long_gap() {
+        let alignment = align("ACDEFGHIKL", "ACDKL", &AlignmentOptions::default());
+        let (_, b) = alignment.aligned_strings("ACDEFGHIKL", "ACDKL");
+        assert_eq!("ACD-----KL", b);
+        assert_eq!(1.0, alignment.identity("ACDEFGHIKL", "ACDKL"));
+    }
+
+    #[test]
+    fn it_align_chains() {
+        let mmtf = load_173d();
+        let options = AlignmentOptions {
+            matrix: SubstitutionMatrix::Nucleotide,
+            ..Default::default()
+        };
+        let pairs = mmtf.chain(0).align_observed(&mmtf.chain(1), &options);
+        assert_eq!((0..8).zip(8..16).collect::<Vec<_>>(), pairs);
+
+        // only the actinomycin residues observed in both chains correspond
+        let pairs = mmtf
+            .chain(2)
+            .align_entity(&mmtf.chain(3), &AlignmentOptions::default());
+        assert_eq!(vec![(21, 22)], pairs);
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+
+use mmtf::Mmtf;
+use selection::AtomSet;
+
+/// Residues are identified by chain, group id and insertion code, so that
+/// the variant groups of micro-heterogeneous residues are handled together.
+type ResidueKey = (usize, i32, char);
+
+/// Occupancy statistics of one alternate location label in a residue
+#[derive(Debug, Default, Clone, Copy)]
+struct Occupancy {
+    sum: f32,
+    count: usize,
+}
+
+impl Occupancy {
+    fn mean(&self) -> f32 {
+        self.sum / self.count as f32
+    }
+}
+
+impl Mmtf {
+    /// Alternate location label of an atom, `None` when the atom has a single position
+    pub fn alt_loc(&self, atom: usize) -> Option<char> {
+        self.alt_loc_list
+            .as_ref()
+            .and_then(|list| list.get(atom).cloned())
+            .and_then(|label| match label {
+                '\0' | ' ' => None,
+                label => Some(label),
+            })
+    }
+
+    /// Distinct alternate location labels of the structure, in ascending order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mut mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// assert!(mmtf.alt_loc_ids().is_empty());
+    ///
+    /// if let Some(ref mut labels) = mmtf.alt_loc_list {
+    ///     labels[3] = 'B';
+    ///     labels[4] = 'A';
+    /// }
+    /// assert_eq!(vec!['A', 'B'], mmtf.alt_loc_ids());
+    /// ```
+    pub fn alt_loc_ids(&self) -> Vec<char> {
+        let mut ids: Vec<char> = (0..self.x_coord_list.len())
+            .filter_map(|atom| self.alt_loc(atom))
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Keep a single conformer: in each residue, the alternate location with
+    /// the highest mean occupancy is kept, ties are resolved by the lowest label.
+    ///
+    /// The alternate location labels of the result are cleared, so that every
+    /// remaining atom has exactly one position.
+    pub fn keep_highest_occupancy(&self) -> Mmtf {
+        self.keep_alt_loc_where(|_| false)
+    }
+
+    /// Keep the conformer with the given alternate location label.
+    ///
+    /// Residues without that label keep their highest occupancy conformer.
+    /// The alternate location labels of the result are cleared.
+    pub fn keep_alt_loc(&self, id: char) -> Mmtf {
+        self.keep_alt_loc_where(|label| label == id)
+    }
+
+    /// Split the structure into one structure for each alternate location label,
+    /// as given by [`alt_loc_ids`](#method.alt_loc_ids).
+    ///
+    /// A structure without alternate locations is returned as a single copy.
+    pub fn split_alt_locs(&self) -> Vec<Mmtf> {
+        let ids = self.alt_loc_ids();
+        if ids.is_empty() {
+            return vec![self.keep_highest_occupancy()];
+        }
+        ids.iter().map(|id| self.keep_alt_loc(*id)).collect()
+    }
+
+    /// Keep the atoms without alternate location and, in each residue, the atoms
+    /// of the label preferred by `prefer` or else of the highest occupancy label.
+    fn keep_alt_loc_where<F: Fn(char) -> bool>(&self, prefer: F) -> Mmtf {
+        let hierarchy = self.hierarchy();
+        let residue = |group: usize| -> ResidueKey {
+            let ins_code = self
+                .ins_code_list
+                .as_ref()
+                .and_then(|codes| codes.get(group).cloned())
+                .unwrap_or('\0');
+            (
+                hierarchy.group_chain[group],
+                self.group_id_list[group],
+                ins_code,
+            )
+        };
+
+        let mut labels: BTreeMap<ResidueKey, BTreeMap<char, Occupancy>> = BTreeMap::new();
+        for atom in 0..hierarchy.num_atoms() {
+            if let Some(label) = self.alt_loc(atom) {
+                let occupancy = self
+                    .occupancy_list
+                    .as_ref()
+                    .and_then(|list| list.get(atom).cloned())
+                    .unwrap_or(1.0);
+                let entry = labels
+                    .entry(residue(hierarchy.atom_group[atom]))
+                    .or_default()
+                    .entry(label)
+                    .or_default();
+                entry.sum += occupancy;
+                entry.count += 1;
+            }
+        }
+
+        let chosen: BTreeMap<ResidueKey, char> = labels
+            .into_iter()
+            .map(|(key, occupancies)| {
+                let preferred = occupancies.keys().find(|label| prefer(**label)).cloned();
+                let highest = occupancies
+                    .iter()
+                    .fold(
+                        None,
+                        |best: Option<(char, f32)>, (label, occupancy)| match best {
+                            Some((_, mean)) if mean >= occupancy.mean() => best,
+                            _ => Some((*label, occupancy.mean())),
+                        },
+                    )
+                    .map(|(label, _)| label);
+                (key, preferred.or(highest).unwrap_or('\0'))
+            })
+            .collect();
+
+        let atoms = AtomSet::from_fn(hierarchy.num_atoms(), |atom| match self.alt_loc(atom) {
+            None => true,
+            Some(label) => chosen.get(&residue(hierarchy.atom_group[atom])) == Some(&label),
+        });
+
+        let mut mmtf = self.select(&atoms);
+        if let Some(ref mut labels) = mmtf.alt_loc_list {
+            for label in labels.iter_mut() {
+                *label = '\0';
+            }
+        }
+        mmtf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    /// 173D with two conformers for part of the first nucleotide, and a
+    /// micro-heterogeneous second residue whose variants are groups 1 and 2
+    fn load_with_alt_locs() -> Mmtf {
+        let mut mmtf = load_173d();
+        let hierarchy = mmtf.hierarchy();
+
+        let mut labels = vec!['\0'; 512];
+        let mut occupancy = vec![1.0; 512];
+        for atom in 5..8 {
+            labels[atom] = 'A';
+            occupancy[atom] = 0.6;
+        }
+        for atom in 8..11 {
+            labels[atom] = 'B';
+            occupancy[atom] = 0.4;
+        }
+        for atom in hierarchy.group_atoms[1].clone() {
+            labels[atom] = 'A';
+            occupancy[atom] = 0.3;
+        }
+        for atom in hierarchy.group_atoms[2].clone() {
+            labels[atom] = 'B';
+            occupancy[atom] = 0.7;
+        }
+        mmtf.group_id_list[2] = mmtf.group_id_list[1];
+        mmtf.alt_loc_list = Some(labels);
+        mmtf.occupancy_list = Some(occupancy);
+        mmtf
+    }
+
+    #[test]
+    fn it_list_alt_loc_ids() {
+        let mmtf = load_with_alt_locs();
+        assert_eq!(vec!['A', 'B'], mmtf.alt_loc_ids());
+        assert_eq!(None, mmtf.alt_loc(0));
+        assert_eq!(Some('B'), mmtf.alt_loc(9));
+    }
+
+    #[test]
+    fn it_keep_highest_occupancy() {
+        let mmtf = load_with_alt_locs();
+        let group_1 = mmtf.hierarchy().group_atoms[1].len();
+        let single = mmtf.keep_highest_occupancy();
+
+        // conformer B of the first group and variant A of the second residue are dropped
+        assert_eq!(512 - 3 - group_1 as i32, single.num_atoms);
+        assert_eq!(123, single.num_groups);
+        assert!(single.alt_loc_ids().is_empty());
+        assert_eq!(single.num_bonds, single.bond_graph().num_bonds() as i32);
+        assert_eq!(mmtf.x_coord_list[11], single.x_coord_list[8]);
+    }
+
+    #[test]
+    fn it_keep_given_alt_loc() {
+        let mmtf = load_with_alt_locs();
+        let group_2 = mmtf.hierarchy().group_atoms[2].len();
+        let conformer = mmtf.keep_alt_loc('B');
+
+        assert_eq!(512 - 3 - group_2 as i32, conformer.num_atoms);
+        assert_eq!(mmtf.x_coord_list[8], conformer.x_coord_list[5]);
+    }
+
+    #[test]
+    fn it_split_alt_locs() {
+        let mmtf = load_with_alt_locs();
+        let conformers = mmtf.split_alt_locs();
+
+        assert_eq!(2, conformers.len());
+        assert_eq!(
+            mmtf.keep_alt_loc('A').x_coord_list,
+            conformers[0].x_coord_list
+        );
+        assert_eq!(
+            mmtf.keep_alt_loc('B').x_coord_list,
+            conformers[1].x_coord_list
+        );
+
+        let plain = load_173d();
+        let copies = plain.split_alt_locs();
+        assert_eq!(1, copies.len());
+        assert_eq!(512, copies[0].num_atoms);
+    }
+}
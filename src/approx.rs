@@ -0,0 +1,314 @@
+use std::io::Read;
+
+use encode::HeaderLayout;
+use mmtf::Mmtf;
+use rmps::decode::Error;
+use stream::StreamDecoder;
+
+/// Largest absolute differences accepted by [`Mmtf::approx_eq`](../mmtf/struct.Mmtf.html#method.approx_eq)
+///
+/// The integer codecs 9 to 13 store a value `v` as `round(v * divisor)`,
+/// so a decoded value may differ from the original by up to one step of
+/// `1 / divisor`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerances {
+    /// Tolerance of the `x`, `y` and `z` coordinates in **Å**
+    pub coordinates: f32,
+
+    /// Tolerance of the B-factors in **Å^2**
+    pub b_factors: f32,
+
+    /// Tolerance of the occupancies
+    pub occupancy: f32,
+}
+
+impl Tolerances {
+    /// Tolerances of values encoded with the given divisors
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mmtf::approx::Tolerances;
+    ///
+    /// let tolerances = Tolerances::from_divisors(1000, 100, 100);
+    /// assert_eq!(Tolerances::default(), tolerances);
+    /// assert_eq!(0.001, tolerances.coordinates);
+    /// ```
+    pub fn from_divisors(coordinates: i32, b_factors: i32, occupancy: i32) -> Self {
+        Tolerances {
+            coordinates: 1.0 / coordinates as f32,
+            b_factors: 1.0 / b_factors as f32,
+            occupancy: 1.0 / occupancy as f32,
+        }
+    }
+
+    /// Tolerances of the codecs and divisors found in the headers of the
+    /// encoded fields of a `MMTF` file
+    ///
+    /// Fields stored without loss, e.g. as plain floats, or missing from the
+    /// file have a tolerance of zero. The coordinate tolerance is the largest
+    /// of the `x`, `y` and `z` fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::approx::Tolerances;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let tolerances = Tolerances::from_reader(File::open(&file_path).unwrap()).unwrap();
+    /// assert_eq!(Tolerances::from_divisors(1000, 100, 100), tolerances);
+    /// ```
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let headers = StreamDecoder::new(reader)
+            .fields(&[
+                "xCoordList",
+                "yCoordList",
+                "zCoordList",
+                "bFactorList",
+                "occupancyList",
+            ])
+            .headers()?;
+        let mut tolerances = Tolerances {
+            coordinates: 0.0,
+            b_factors: 0.0,
+            occupancy: 0.0,
+        };
+        for (name, header) in headers {
+            let step = quantization_step(&header);
+            match name.as_str() {
+                "bFactorList" => tolerances.b_factors = step,
+                "occupancyList" => tolerances.occupancy = step,
+                _ => tolerances.coordinates = tolerances.coordinates.max(step),
+            }
+        }
+        Ok(tolerances)
+    }
+}
+
+/// Largest loss of the integer codecs 9 to 13, zero for the others
+fn quantization_step(header: &HeaderLayout) -> f32 {
+    match header.codec {
+        9..=13 if header.parameter > 0 => 1.0 / header.parameter as f32,
+        _ => 0.0,
+    }
+}
+
+impl Default for Tolerances {
+    /// Tolerances of the divisors recommended by the MMTF specification,
+    /// 1000 for coordinates and 100 for B-factors and occupancies
+    fn default() -> Self {
+        Tolerances::from_divisors(1000, 100, 100)
+    }
+}
+
+fn approx_eq_list(a: &[f32], b: &[f32], tolerance: f32) -> bool {
+    // allow for the rounding of the f32 arithmetic on top of the quantization
+    let tolerance = tolerance * (1.0 + 1e-4);
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() <= tolerance)
+}
+
+fn approx_eq_option(a: &Option<Vec<f32>>, b: &Option<Vec<f32>>, tolerance: f32) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => approx_eq_list(a, b, tolerance),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+impl Mmtf {
+    /// Whether the structures are equal up to the loss of the integer codecs
+    ///
+    /// Coordinates, B-factors and occupancies are compared within the given
+    /// [`Tolerances`](../approx/struct.Tolerances.html), all other fields
+    /// must be equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    /// use mmtf::approx::Tolerances;
+    /// use mmtf::codec::IntegerDeltaRecursive;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    ///
+    /// let mut coarse = mmtf.clone();
+    /// let encoded = IntegerDeltaRecursive::encode(&mmtf.x_coord_list, 10).unwrap();
+    /// coarse.x_coord_list = IntegerDeltaRecursive::decode(&encoded, 10).unwrap();
+    ///
+    /// assert!(mmtf != coarse);
+    /// assert!(mmtf.approx_eq(&coarse, Tolerances::from_divisors(10, 100, 100)));
+    /// ```
+    pub fn approx_eq(&self, other: &Mmtf, tolerances: Tolerances) -> bool {
+        // no `..` in the pattern, so that new fields must be compared as well
+        let Mmtf {
+            mmtf_version,
+            mmtf_producer,
+            unit_cell,
+            space_group,
+            structure_id,
+            title,
+            deposition_date,
+            release_date,
+            ncs_operator_list,
+            bio_assembly_list,
+            entity_list,
+            experimental_methods,
+            resolution,
+            r_free,
+            r_work,
+            num_bonds,
+            num_atoms,
+            num_groups,
+            num_chains,
+            num_models,
+            group_list,
+            bond_atom_list,
+            bond_order_list,
+            x_coord_list,
+            y_coord_list,
+            z_coord_list,
+            b_factor_list,
+            atom_id_list,
+            alt_loc_list,
+            occupancy_list,
+            group_id_list,
+            group_type_list,
+            sec_struct_list,
+            ins_code_list,
+            sequence_index_list,
+            chain_id_list,
+            chain_name_list,
+            groups_per_chain,
+            chains_per_model,
+        } = self;
+
+        let coordinates = tolerances.coordinates;
+        approx_eq_list(x_coord_list, &other.x_coord_list, coordinates)
+            && approx_eq_list(y_coord_list, &other.y_coord_list, coordinates)
+            && approx_eq_list(z_coord_list, &other.z_coord_list, coordinates)
+            && approx_eq_option(b_factor_list, &other.b_factor_list, tolerances.b_factors)
+            && approx_eq_option(occupancy_list, &other.occupancy_list, tolerances.occupancy)
+            && *mmtf_version == other.mmtf_version
+            && *mmtf_producer == other.mmtf_producer
+            && *unit_cell == other.unit_cell
+            && *space_group == other.space_group
+            && *structure_id == other.structure_id
+            && *title == other.title
+            && *deposition_date == other.deposition_date
+            && *release_date == other.release_date
+            && *ncs_operator_list == other.ncs_operator_list
+            && *bio_assembly_list == other.bio_assembly_list
+            && *entity_list == other.entity_list
+            && *experimental_methods == other.experimental_methods
+            && *resolution == other.resolution
+            && *r_free == other.r_free
+            && *r_work == other.r_work
+            && *num_bonds == other.num_bonds
+            && *num_atoms == other.num_atoms
+            && *num_groups == other.num_groups
+            && *num_chains == other.num_chains
+            && *num_models == other.num_models
+            && *group_list == other.group_list
+            && *bond_atom_list == other.bond_atom_list
+            && *bond_order_list == other.bond_order_list
+            && *atom_id_list == other.atom_id_list
+            && *alt_loc_list == other.alt_loc_list
+            && *group_id_list == other.group_id_list
+            && *group_type_list == other.group_type_list
+            && *sec_struct_list == other.sec_struct_list
+            && *ins_code_list == other.ins_code_list
+            && *sequence_index_list == other.sequence_index_list
+            && *chain_id_list == other.chain_id_list
+            && *chain_name_list == other.chain_name_list
+            && *groups_per_chain == other.groups_per_chain
+            && *chains_per_model == other.chains_per_model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codec::{IntegerDeltaRecursive, IntegerRunLength};
+    use test_support::{load_173d, open_173d};
+
+    fn round_trip(mmtf: &Mmtf, coordinates: i32, b_factors: i32, occupancy: i32) -> Mmtf {
+        let coords = |list: &[f32]| {
+            let encoded = IntegerDeltaRecursive::encode(list, coordinates).unwrap();
+            IntegerDeltaRecursive::decode(&encoded, coordinates).unwrap()
+        };
+        let mut decoded = mmtf.clone();
+        decoded.x_coord_list = coords(&mmtf.x_coord_list);
+        decoded.y_coord_list = coords(&mmtf.y_coord_list);
+        decoded.z_coord_list = coords(&mmtf.z_coord_list);
+        decoded.b_factor_list = mmtf.b_factor_list.as_ref().map(|list| {
+            let encoded = IntegerDeltaRecursive::encode(list, b_factors).unwrap();
+            IntegerDeltaRecursive::decode(&encoded, b_factors).unwrap()
+        });
+        decoded.occupancy_list = mmtf.occupancy_list.as_ref().map(|list| {
+            let encoded = IntegerRunLength::encode(list, occupancy).unwrap();
+            IntegerRunLength::decode(&encoded, occupancy).unwrap()
+        });
+        decoded
+    }
+
+    #[test]
+    fn it_compare_round_trips_with_default_divisors() {
+        let mmtf = load_173d();
+        let decoded = round_trip(&mmtf, 1000, 100, 100);
+        assert!(mmtf.approx_eq(&decoded, Tolerances::default()));
+    }
+
+    #[test]
+    fn it_read_tolerances_from_codec_headers() {
+        let tolerances = Tolerances::from_reader(open_173d()).unwrap();
+        let mmtf = load_173d();
+        assert!(mmtf.approx_eq(&round_trip(&mmtf, 1000, 100, 100), tolerances));
+
+        let header = |codec, parameter| HeaderLayout {
+            codec,
+            length: 0,
+            parameter,
+        };
+        assert_eq!(0.1, quantization_step(&header(10, 10)));
+        assert_eq!(0.0, quantization_step(&header(1, 0)));
+        assert_eq!(0.0, quantization_step(&header(9, 0)));
+    }
+
+    #[test]
+    fn it_derive_tolerances_from_divisors() {
+        let mmtf = load_173d();
+        let decoded = round_trip(&mmtf, 10, 10, 10);
+        assert_ne!(mmtf, decoded);
+        assert!(!mmtf.approx_eq(&decoded, Tolerances::default()));
+        assert!(mmtf.approx_eq(&decoded, Tolerances::from_divisors(10, 10, 10)));
+    }
+
+    #[test]
+    fn it_require_equal_exact_fields() {
+        let mmtf = load_173d();
+        let mut other = mmtf.clone();
+        other.group_list[0].group_name = "XYZ".to_string();
+        assert!(!mmtf.approx_eq(&other, Tolerances::default()));
+
+        let mut other = mmtf.clone();
+        other.b_factor_list = None;
+        assert!(!mmtf.approx_eq(&other, Tolerances::default()));
+
+        let mut other = mmtf.clone();
+        other.x_coord_list.pop();
+        assert!(!mmtf.approx_eq(&other, Tolerances::default()));
+    }
+}
@@ -0,0 +1,684 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use mmtf::Mmtf;
+use neighbors::NeighborSearch;
+
+/// Covalent bond perception from atomic coordinates
+///
+/// Two atoms are considered bonded when their distance lies between
+/// [`min_distance`](#structfield.min_distance) and the sum of their
+/// covalent radii plus [`tolerance`](#structfield.tolerance).
+/// Atoms of different models and atoms with different alternate location
+/// labels are never bonded. Atoms whose element is unknown are skipped.
+///
+/// All perceived bonds are single bonds (order 1), since bond orders can
+/// not be derived from distances alone.
+///
+/// # Examples
+///
+/// ```
+/// # use std::path::Path;
+/// # use std::env;
+/// use std::fs::File;
+/// use mmtf::Mmtf;
+/// use mmtf::bonds::BondPerception;
+///
+/// # let file_path = Path::new(&env::current_dir().unwrap())
+/// #                                    .join("tests")
+/// #                                    .join("data")
+/// #                                    .join("173D.mmtf");
+/// let mut mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+///
+/// let bonds = BondPerception::default().find_bonds(&mmtf);
+/// assert!(bonds.contains(&(0, 1)));
+///
+/// mmtf.perceive_bonds();
+/// assert_eq!(458, mmtf.num_bonds);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BondPerception {
+    /// Distance in **Å** added to the sum of the covalent radii
+    pub tolerance: f32,
+
+    /// Pairs of atoms closer than this distance in **Å** are not bonded
+    pub min_distance: f32,
+}
+
+impl Default for BondPerception {
+    fn default() -> Self {
+        BondPerception {
+            tolerance: 0.45,
+            min_distance: 0.4,
+        }
+    }
+}
+
+impl BondPerception {
+    /// Find all covalent bonds of the structure as pairs of atom indices,
+    /// with the lower index first and sorted in ascending order.
+    pub fn find_bonds(&self, mmtf: &Mmtf) -> Vec<(usize, usize)> {
+        let hierarchy = mmtf.hierarchy();
+        let radii: Vec<Option<f32>> = mmtf.atom_elements()
+            .iter()
+            .map(|e| e.map(|e| e.covalent_radius()))
+            .collect();
+        let max_radius = radii
+            .iter()
+            .filter_map(|r| *r)
+            .fold(0.0_f32, f32::max);
+        let cell_size = (2.0 * max_radius + self.tolerance).max(1.0);
+
+        let alt_loc = |atom: usize| {
+            mmtf.alt_loc_list
+                .as_ref()
+                .and_then(|list| list.get(atom).cloned())
+                .unwrap_or('\0')
+        };
+
+        let mut bonds = Vec::new();
+        for model in 0..hierarchy.num_models() {
+            let atoms = hierarchy
+                .model_atoms(model)
+                .filter(|atom| radii[*atom].is_some());
+            let search = NeighborSearch::with_atoms(mmtf, atoms, cell_size);
+            for (atom, other, distance) in search.pairs_within(cell_size) {
+                let (a, b) = (alt_loc(atom), alt_loc(other));
+                if a != '\0' && b != '\0' && a != b {
+                    continue;
+                }
+                let cutoff =
+                    radii[atom].unwrap_or(0.0) + radii[other].unwrap_or(0.0) + self.tolerance;
+                if distance >= self.min_distance && distance <= cutoff {
+                    bonds.push((atom, other));
+                }
+            }
+        }
+        bonds.sort();
+        bonds
+    }
+
+    /// Perceive bonds and add them to the structure.
+    ///
+    /// Intra-group bonds are only added to group types without any bonds in
+    /// [`GroupType.bond_atom_list`](../mmtf/struct.GroupType.html#structfield.bond_atom_list).
+    /// As group types are shared by all groups of the same kind, a bond is added
+    /// when it is found in at least half of the groups using that group type.
+    /// Inter-group bonds which are not yet present in
+    /// [`Mmtf.bond_atom_list`](../mmtf/struct.Mmtf.html#structfield.bond_atom_list)
+    /// are appended to it. Finally [`Mmtf.num_bonds`](../mmtf/struct.Mmtf.html#structfield.num_bonds)
+    /// is updated to reflect both lists.
+    pub fn apply(&self, mmtf: &mut Mmtf) {
+        let bonds = self.find_bonds(mmtf);
+        let hierarchy = mmtf.hierarchy();
+
+        let mut instances: HashMap<usize, usize> = HashMap::new();
+        for group_type in &mmtf.group_type_list {
+            *instances.entry(*group_type as usize).or_insert(0) += 1;
+        }
+
+        let mut existing: HashSet<(usize, usize)> = mmtf.bond_atom_list
+            .chunks(2)
+            .filter(|pair| pair.len() == 2)
+            .map(|pair| {
+                let (a, b) = (pair[0] as usize, pair[1] as usize);
+                (a.min(b), a.max(b))
+            })
+            .collect();
+
+        let mut intra: HashMap<usize, HashMap<(usize, usize), usize>> = HashMap::new();
+        let mut inter = Vec::new();
+        for (a, b) in bonds {
+            let group = hierarchy.atom_group[a];
+            if group == hierarchy.atom_group[b] {
+                let group_type = mmtf.group_type_list[group] as usize;
+                if !mmtf.group_list[group_type].bond_atom_list.is_empty() {
+                    continue;
+                }
+                let offset = hierarchy.group_atoms[group].start;
+                *intra
+                    .entry(group_type)
+                    .or_default()
+                    .entry((a - offset, b - offset))
+                    .or_insert(0) += 1;
+            } else if existing.insert((a, b)) {
+                inter.push((a, b));
+            }
+        }
+
+        for (group_type, counts) in intra {
+            let required = instances[&group_type].div_ceil(2);
+            let mut pairs: Vec<(usize, usize)> = counts
+                .into_iter()
+                .filter(|&(_, count)| count >= required)
+                .map(|(pair, _)| pair)
+                .collect();
+            pairs.sort();
+
+            let group = &mut mmtf.group_list[group_type];
+            for (a, b) in pairs {
+                group.bond_atom_list.push(a as i32);
+                group.bond_atom_list.push(b as i32);
+                group.bond_order_list.push(1);
+            }
+        }
+
+        let had_bonds = !mmtf.bond_atom_list.is_empty();
+        for &(a, b) in &inter {
+            mmtf.bond_atom_list.push(a as i32);
+            mmtf.bond_atom_list.push(b as i32);
+        }
+        match mmtf.bond_order_list {
+            Some(ref mut orders) => orders.extend(inter.iter().map(|_| 1)),
+            None if !had_bonds => mmtf.bond_order_list = Some(vec![1; inter.len()]),
+            None => (),
+        }
+
+        mmtf.num_bonds = count_bonds(mmtf);
+    }
+}
+
+/// Number of bonds in the group types of all groups plus the inter-group bonds
+pub fn count_bonds(mmtf: &Mmtf) -> i32 {
+    let intra: usize = mmtf.group_type_list
+        .iter()
+        .map(|group_type| mmtf.group_list[*group_type as usize].bond_atom_list.len() / 2)
+        .sum();
+    (intra + mmtf.bond_atom_list.len() / 2) as i32
+}
+
+/// A covalent bond between two atoms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bond {
+    /// Index of the first atom, always lower than `atom2`
+    pub atom1: usize,
+
+    /// Index of the second atom
+    pub atom2: usize,
+
+    /// Bond order between 1 and 4, `None` when not given in the file
+    pub order: Option<i32>,
+}
+
+/// Bonds of the whole structure as a graph over global atom indices
+///
+/// Group-local bonds of [`GroupType.bond_atom_list`](../mmtf/struct.GroupType.html#structfield.bond_atom_list)
+/// are expanded for each group, and merged with the inter-group bonds of
+/// [`Mmtf.bond_atom_list`](../mmtf/struct.Mmtf.html#structfield.bond_atom_list).
+/// Bonds listed more than once are only kept once.
+///
+/// # Examples
+///
+/// ```
+/// # use std::path::Path;
+/// # use std::env;
+/// use std::fs::File;
+/// use mmtf::Mmtf;
+///
+/// # let file_path = Path::new(&env::current_dir().unwrap())
+/// #                                    .join("tests")
+/// #                                    .join("data")
+/// #                                    .join("173D.mmtf");
+/// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+/// let graph = mmtf.bond_graph();
+///
+/// assert_eq!(mmtf.num_bonds as usize, graph.num_bonds());
+/// assert_eq!(vec![0, 2], graph.neighbors(1).collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BondGraph {
+    bonds: Vec<Bond>,
+    adjacency: Vec<Vec<(usize, usize)>>,
+}
+
+impl BondGraph {
+    /// Build the bond graph of the given structure
+    pub fn new(mmtf: &Mmtf) -> Self {
+        let hierarchy = mmtf.hierarchy();
+        let mut bonds: Vec<Bond> = Vec::with_capacity(mmtf.num_bonds.max(0) as usize);
+
+        for (group, group_type) in mmtf.group_type_list.iter().enumerate() {
+            let group_type = &mmtf.group_list[*group_type as usize];
+            let offset = hierarchy.group_atoms[group].start;
+            for (index, pair) in group_type.bond_atom_list.chunks(2).enumerate() {
+                if pair.len() == 2 {
+                    let order = group_type.bond_order_list.get(index).cloned();
+                    bonds.push(Bond::new(
+                        offset + pair[0] as usize,
+                        offset + pair[1] as usize,
+                        order,
+                    ));
+                }
+            }
+        }
+
+        for (index, pair) in mmtf.bond_atom_list.chunks(2).enumerate() {
+            if pair.len() == 2 {
+                let order = mmtf.bond_order_list
+                    .as_ref()
+                    .and_then(|orders| orders.get(index))
+                    .map(|order| i32::from(*order));
+                bonds.push(Bond::new(pair[0] as usize, pair[1] as usize, order));
+            }
+        }
+
+        BondGraph::from_bonds(hierarchy.num_atoms(), bonds)
+    }
+
+    /// Build a bond graph over `num_atoms` atoms from a list of bonds.
+    /// Duplicated bonds are dropped, keeping the first given bond order.
+    pub fn from_bonds(num_atoms: usize, bonds: Vec<Bond>) -> Self {
+        let mut seen = HashSet::with_capacity(bonds.len());
+        let bonds: Vec<Bond> = bonds
+            .into_iter()
+            .filter(|bond| bond.atom1 != bond.atom2 && bond.atom2 < num_atoms)
+            .filter(|bond| seen.insert((bond.atom1, bond.atom2)))
+            .collect();
+
+        let mut adjacency = vec![Vec::new(); num_atoms];
+        for (index, bond) in bonds.iter().enumerate() {
+            adjacency[bond.atom1].push((bond.atom2, index));
+            adjacency[bond.atom2].push((bond.atom1, index));
+        }
+        for neighbors in &mut adjacency {
+            neighbors.sort();
+        }
+
+        BondGraph { bonds, adjacency }
+    }
+
+    /// Number of atoms (nodes)
+    pub fn num_atoms(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// Number of bonds (edges)
+    pub fn num_bonds(&self) -> usize {
+        self.bonds.len()
+    }
+
+    /// All bonds of the graph
+    pub fn bonds(&self) -> &[Bond] {
+        &self.bonds
+    }
+
+    /// Atoms bonded to the given atom, in ascending order
+    pub fn neighbors<'a>(&'a self, atom: usize) -> impl Iterator<Item = usize> + 'a {
+        self.adjacency[atom].iter().map(|&(neighbor, _)| neighbor)
+    }
+
+    /// Bonds of the given atom
+    pub fn atom_bonds<'a>(&'a self, atom: usize) -> impl Iterator<Item = &'a Bond> + 'a {
+        self.adjacency[atom].iter().map(move |&(_, bond)| &self.bonds[bond])
+    }
+
+    /// Number of bonds of the given atom
+    pub fn degree(&self, atom: usize) -> usize {
+        self.adjacency[atom].len()
+    }
+
+    /// The bond between two atoms, if any
+    pub fn bond(&self, atom1: usize, atom2: usize) -> Option<&Bond> {
+        self.adjacency[atom1]
+            .iter()
+            .find(|&&(neighbor, _)| neighbor == atom2)
+            .map(|&(_, bond)| &self.bonds[bond])
+    }
+
+    /// Connected components of the graph, i.e. the molecules of the structure.
+    /// Each component is a sorted list of atom indices, unbonded atoms form
+    /// components of their own.
+    pub fn connected_components(&self) -> Vec<Vec<usize>> {
+        self.components(|_| true)
+    }
+
+    /// Smallest set of smallest rings (SSSR)
+    ///
+    /// Each ring is given as atom indices in ring order. The rings are chosen
+    /// greedily by size among the shortest cycles through each bond, keeping
+    /// only rings which are independent of the already selected ones.
+    pub fn rings(&self) -> Vec<Vec<usize>> {
+        let bridges = self.bridges();
+        let cyclic = |bond: usize| !bridges[bond];
+
+        let mut rings = Vec::new();
+        for system in self.components(cyclic) {
+            if system.len() < 3 {
+                continue;
+            }
+            let mut edges: Vec<usize> = system
+                .iter()
+                .flat_map(|atom| self.adjacency[*atom].iter().map(|&(_, bond)| bond))
+                .filter(|bond| cyclic(*bond))
+                .collect();
+            edges.sort();
+            edges.dedup();
+            let local: HashMap<usize, usize> =
+                edges.iter().enumerate().map(|(i, bond)| (*bond, i)).collect();
+
+            let mut candidates: Vec<(Vec<usize>, Vec<bool>)> = edges
+                .iter()
+                .filter_map(|bond| self.shortest_cycle(*bond, &cyclic))
+                .map(|(atoms, bonds)| {
+                    let mut vector = vec![false; edges.len()];
+                    for bond in bonds {
+                        vector[local[&bond]] = true;
+                    }
+                    (atoms, vector)
+                })
+                .collect();
+            candidates.sort_by_key(|candidate| candidate.0.len());
+            candidates.dedup_by(|a, b| a.1 == b.1);
+
+            let needed = edges.len() + 1 - system.len();
+            let mut basis: Vec<(usize, Vec<bool>)> = Vec::new();
+            for (atoms, vector) in candidates {
+                if basis.len() == needed {
+                    break;
+                }
+                let mut reduced = vector;
+                for &(pivot, ref row) in &basis {
+                    if reduced[pivot] {
+                        for (r, b) in reduced.iter_mut().zip(row) {
+                            *r ^= *b;
+                        }
+                    }
+                }
+                if let Some(pivot) = reduced.iter().position(|b| *b) {
+                    basis.push((pivot, reduced));
+                    rings.push(atoms);
+                }
+            }
+        }
+        rings
+    }
+
+    /// Connected components only following bonds accepted by `follow`
+    fn components<F: Fn(usize) -> bool>(&self, follow: F) -> Vec<Vec<usize>> {
+        let mut visited = vec![false; self.num_atoms()];
+        let mut components = Vec::new();
+        for start in 0..self.num_atoms() {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let mut component = vec![start];
+            let mut stack = vec![start];
+            while let Some(atom) = stack.pop() {
+                for &(neighbor, bond) in &self.adjacency[atom] {
+                    if !visited[neighbor] && follow(bond) {
+                        visited[neighbor] = true;
+                        component.push(neighbor);
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+        components
+    }
+
+    /// Flag bonds whose removal disconnects the graph, using an iterative
+    /// version of Tarjan's algorithm to handle long polymer chains.
+    fn bridges(&self) -> Vec<bool> {
+        let unvisited = usize::MAX;
+        let mut discovery = vec![unvisited; self.num_atoms()];
+        let mut low = vec![0; self.num_atoms()];
+        let mut bridges = vec![false; self.num_bonds()];
+        let mut time = 0;
+
+        for root in 0..self.num_atoms() {
+            if discovery[root] != unvisited {
+                continue;
+            }
+            discovery[root] = time;
+            low[root] = time;
+            time += 1;
+            // (atom, bond used to reach the atom, next neighbor to visit)
+            let mut stack = vec![(root, unvisited, 0)];
+
+            while let Some(&mut (atom, parent_bond, ref mut next)) = stack.last_mut() {
+                if let Some(&(neighbor, bond)) = self.adjacency[atom].get(*next) {
+                    *next += 1;
+                    if bond == parent_bond {
+                        continue;
+                    }
+                    if discovery[neighbor] == unvisited {
+                        discovery[neighbor] = time;
+                        low[neighbor] = time;
+                        time += 1;
+                        stack.push((neighbor, bond, 0));
+                    } else {
+                        low[atom] = low[atom].min(discovery[neighbor]);
+                    }
+                } else {
+                    stack.pop();
+                    if let Some(&(parent, _, _)) = stack.last() {
+                        low[parent] = low[parent].min(low[atom]);
+                        if low[atom] > discovery[parent] {
+                            bridges[parent_bond] = true;
+                        }
+                    }
+                }
+            }
+        }
+        bridges
+    }
+
+    /// Shortest cycle through `bond`, as ordered atoms and the bonds used
+    fn shortest_cycle<F: Fn(usize) -> bool>(
+        &self,
+        bond: usize,
+        follow: &F,
+    ) -> Option<(Vec<usize>, Vec<usize>)> {
+        let Bond { atom1, atom2, .. } = self.bonds[bond];
+        let mut previous: HashMap<usize, (usize, usize)> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(atom1);
+        previous.insert(atom1, (atom1, bond));
+
+        while let Some(atom) = queue.pop_front() {
+            if atom == atom2 {
+                let mut atoms = vec![atom2];
+                let mut bonds = vec![bond];
+                let mut current = atom2;
+                while current != atom1 {
+                    let (parent, via) = previous[&current];
+                    bonds.push(via);
+                    atoms.push(parent);
+                    current = parent;
+                }
+                return Some((atoms, bonds));
+            }
+            for &(neighbor, via) in &self.adjacency[atom] {
+                if via != bond && follow(via) && !previous.contains_key(&neighbor) {
+                    previous.insert(neighbor, (atom, via));
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Bond {
+    /// Create a bond, ordering the atoms by index
+    pub fn new(atom1: usize, atom2: usize, order: Option<i32>) -> Self {
+        Bond {
+            atom1: atom1.min(atom2),
+            atom2: atom1.max(atom2),
+            order,
+        }
+    }
+}
+
+impl Mmtf {
+    /// Perceive missing bonds with the default [`BondPerception`](../bonds/struct.BondPerception.html)
+    /// parameters.
+    pub fn perceive_bonds(&mut self) {
+        BondPerception::default().apply(self)
+    }
+
+    /// Build the [`BondGraph`](../bonds/struct.BondGraph.html) of all bonds in the structure
+    pub fn bond_graph(&self) -> BondGraph {
+        BondGraph::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    fn strip_bonds(mmtf: &mut Mmtf) {
+        for group in &mut mmtf.group_list {
+            group.bond_atom_list.clear();
+            group.bond_order_list.clear();
+        }
+        mmtf.bond_atom_list.clear();
+        mmtf.bond_order_list = None;
+        mmtf.num_bonds = 0;
+    }
+
+    fn sorted_pairs(list: &[i32]) -> Vec<(i32, i32)> {
+        let mut pairs: Vec<(i32, i32)> = list.chunks(2)
+            .map(|p| (p[0].min(p[1]), p[0].max(p[1])))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn it_count_bonds() {
+        let mmtf = load_173d();
+        assert_eq!(mmtf.num_bonds, count_bonds(&mmtf));
+    }
+
+    #[test]
+    fn it_keep_existing_bonds() {
+        let mut mmtf = load_173d();
+        let inter = mmtf.bond_atom_list.clone();
+        mmtf.perceive_bonds();
+
+        assert_eq!(inter, mmtf.bond_atom_list);
+        assert_eq!(Some(26), mmtf.bond_order_list.as_ref().map(Vec::len));
+        assert_eq!(458, mmtf.num_bonds);
+    }
+
+    #[test]
+    fn it_recover_stripped_bonds() {
+        let original = load_173d();
+        let mut mmtf = load_173d();
+        strip_bonds(&mut mmtf);
+        mmtf.perceive_bonds();
+
+        for (perceived, expected) in mmtf.group_list.iter().zip(&original.group_list) {
+            let perceived_pairs = sorted_pairs(&perceived.bond_atom_list);
+            for pair in sorted_pairs(&expected.bond_atom_list) {
+                assert!(perceived_pairs.contains(&pair), "{} {:?}", expected.group_name, pair);
+            }
+            assert!(perceived.bond_order_list.iter().all(|o| *o == 1));
+        }
+        assert_eq!(
+            sorted_pairs(&original.bond_atom_list),
+            sorted_pairs(&mmtf.bond_atom_list)
+        );
+        assert_eq!(Some(vec![1; 26]), mmtf.bond_order_list);
+        assert_eq!(count_bonds(&mmtf), mmtf.num_bonds);
+        assert!(mmtf.num_bonds >= original.num_bonds);
+    }
+
+    #[test]
+    fn it_ignore_different_alt_locs() {
+        let mut mmtf = load_173d();
+        let mut alt_locs = vec!['\0'; 512];
+        alt_locs[0] = 'A';
+        alt_locs[1] = 'B';
+        mmtf.alt_loc_list = Some(alt_locs);
+
+        let bonds = BondPerception::default().find_bonds(&mmtf);
+        assert!(!bonds.contains(&(0, 1)));
+        assert!(bonds.contains(&(1, 2)));
+    }
+
+    fn graph(num_atoms: usize, pairs: &[(usize, usize)]) -> BondGraph {
+        let bonds = pairs.iter().map(|&(a, b)| Bond::new(a, b, Some(1))).collect();
+        BondGraph::from_bonds(num_atoms, bonds)
+    }
+
+    #[test]
+    fn it_deduplicate_bonds() {
+        let graph = BondGraph::from_bonds(
+            3,
+            vec![
+                Bond::new(0, 1, Some(2)),
+                Bond::new(1, 0, Some(1)),
+                Bond::new(1, 2, None),
+                Bond::new(2, 2, None),
+            ],
+        );
+        assert_eq!(2, graph.num_bonds());
+        assert_eq!(Some(2), graph.bond(1, 0).unwrap().order);
+        assert_eq!(None, graph.bond(0, 2));
+        assert_eq!(vec![0, 2], graph.neighbors(1).collect::<Vec<_>>());
+        assert_eq!(2, graph.degree(1));
+    }
+
+    #[test]
+    fn it_find_connected_components() {
+        let graph = graph(6, &[(0, 1), (1, 2), (4, 3)]);
+        assert_eq!(
+            vec![vec![0, 1, 2], vec![3, 4], vec![5]],
+            graph.connected_components()
+        );
+    }
+
+    #[test]
+    fn it_find_fused_rings() {
+        // naphthalene, with a methyl group attached
+        let graph = graph(
+            11,
+            &[
+                (0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0),
+                (4, 6), (6, 7), (7, 8), (8, 9), (9, 5), (9, 10),
+            ],
+        );
+        let mut rings: Vec<Vec<usize>> = graph
+            .rings()
+            .into_iter()
+            .map(|mut ring| {
+                ring.sort();
+                ring
+            })
+            .collect();
+        rings.sort();
+        assert_eq!(vec![vec![0, 1, 2, 3, 4, 5], vec![4, 5, 6, 7, 8, 9]], rings);
+    }
+
+    #[test]
+    fn it_find_no_rings_in_trees() {
+        let graph = graph(5, &[(0, 1), (1, 2), (1, 3), (3, 4)]);
+        assert!(graph.rings().is_empty());
+    }
+
+    #[test]
+    fn it_build_bond_graph() {
+        let mmtf = load_173d();
+        let graph = mmtf.bond_graph();
+
+        assert_eq!(512, graph.num_atoms());
+        assert_eq!(458, graph.num_bonds());
+
+        // two DNA strands, two actinomycin peptides and 96 waters
+        let components = graph.connected_components();
+        assert_eq!(100, components.len());
+        assert_eq!(4, components.iter().filter(|c| c.len() > 1).count());
+
+        // sugar and base rings of the DNA, proline and lactone rings of
+        // the peptides and the phenoxazone rings
+        let rings = graph.rings();
+        assert_eq!(46, rings.len());
+        assert_eq!(40, rings.iter().filter(|r| *r.iter().max().unwrap() < 322).count());
+        assert_eq!(2, rings.iter().filter(|r| r.len() == 16).count());
+    }
+}
@@ -0,0 +1,265 @@
+use std::ops::Range;
+
+use mmtf::{Entity, Mmtf};
+use residue::Residue;
+
+/// Gap character of [`Chain::observed_sequence`](struct.Chain.html#method.observed_sequence)
+pub const GAP: char = '-';
+
+/// Residue code of groups without single letter code
+pub const UNKNOWN: char = 'X';
+
+/// View on a single chain of a structure
+///
+/// # Examples
+///
+/// ```
+/// # use std::path::Path;
+/// # use std::env;
+/// use std::fs::File;
+/// use mmtf::Mmtf;
+///
+/// # let file_path = Path::new(&env::current_dir().unwrap())
+/// #                                    .join("tests")
+/// #                                    .join("data")
+/// #                                    .join("173D.mmtf");
+/// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+/// let chain = mmtf.chain(3);
+///
+/// assert_eq!("D", chain.id());
+/// assert_eq!(Some("TXPGVXTXPGV"), chain.sequence());
+/// assert_eq!(Some("-----XTXPGV".to_string()), chain.observed_sequence());
+/// assert_eq!(0..5, chain.n_terminal_truncation());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Chain<'a> {
+    mmtf: &'a Mmtf,
+    index: usize,
+    groups: Range<usize>,
+}
+
+impl<'a> Chain<'a> {
+    /// View on the chain with the given index
+    pub fn new(mmtf: &'a Mmtf, index: usize) -> Self {
+        let start: usize = mmtf.groups_per_chain[..index]
+            .iter()
+            .map(|n| *n as usize)
+            .sum();
+        let end = start + mmtf.groups_per_chain[index] as usize;
+        Chain {
+            mmtf,
+            index,
+            groups: start..end,
+        }
+    }
+
+    /// Index of the chain in the structure
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Chain id, see [`Mmtf.chain_id_list`](../mmtf/struct.Mmtf.html#structfield.chain_id_list)
+    pub fn id(&self) -> &'a str {
+        &self.mmtf.chain_id_list[self.index]
+    }
+
+    /// Chain name, see [`Mmtf.chain_name_list`](../mmtf/struct.Mmtf.html#structfield.chain_name_list)
+    pub fn name(&self) -> Option<&'a str> {
+        self.mmtf
+            .chain_name_list
+            .as_ref()
+            .map(|names| names[self.index].as_str())
+    }
+
+    /// Range of group indices of the chain
+    pub fn groups(&self) -> Range<usize> {
+        self.groups.clone()
+    }
+
+    /// Residues of the chain, see [`Mmtf::chain_residues`](../mmtf/struct.Mmtf.html#method.chain_residues)
+    pub fn residues(&self) -> Vec<Residue> {
+        self.mmtf.group_residues(self.index, self.groups())
+    }
+
+    /// First variant group and single letter code of each residue of the chain
+    ///
+    /// Groups without single letter code are given as [`UNKNOWN`](constant.UNKNOWN.html).
+    pub fn residue_codes(&self) -> Vec<(usize, char)> {
+        self.residues()
+            .iter()
+            .map(|residue| {
+                let group = residue.groups[0];
+                let group_type = self.mmtf.group_type_list[group] as usize;
+                let code = &self.mmtf.group_list[group_type].single_letter_code;
+                match code.chars().next() {
+                    Some(code) if code != '?' => (group, code),
+                    _ => (group, UNKNOWN),
+                }
+            })
+            .collect()
+    }
+
+    /// Entity the chain belongs to
+    pub fn entity(&self) -> Option<&'a Entity> {
+        self.mmtf.entity_list.as_ref().and_then(|entities| {
+            entities
+                .iter()
+                .find(|entity| entity.chain_index_list.contains(&(self.index as i32)))
+        })
+    }
+
+    /// Sequence of the full construct, as given by the entity
+    pub fn sequence(&self) -> Option<&'a str> {
+        self.entity().map(|entity| entity.sequence.as_str())
+    }
+
+    /// Observed group of each position of the entity sequence
+    ///
+    /// For micro-heterogeneous residues the first variant group is given.
+    /// Positions without any group in the chain are unresolved and `None`.
+    pub fn sequence_groups(&self) -> Vec<Option<usize>> {
+        let length = self
+            .sequence()
+            .map_or(0, |sequence| sequence.chars().count());
+        let mut positions = vec![None; length];
+        if let Some(ref sequence_index) = self.mmtf.sequence_index_list {
+            for group in self.groups() {
+                let index = sequence_index[group];
+                if index >= 0 && (index as usize) < length && positions[index as usize].is_none() {
+                    positions[index as usize] = Some(group);
+                }
+            }
+        }
+        positions
+    }
+
+    /// Ranges of sequence positions without observed group, including the termini
+    pub fn missing_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for (position, group) in self.sequence_groups().iter().enumerate() {
+            if group.is_some() {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(range) if range.end == position => range.end += 1,
+                _ => ranges.push(position..position + 1),
+            }
+        }
+        ranges
+    }
+
+    /// Unresolved sequence positions before the first observed group
+    pub fn n_terminal_truncation(&self) -> Range<usize> {
+        let groups = self.sequence_groups();
+        0..groups
+            .iter()
+            .position(Option::is_some)
+            .unwrap_or(groups.len())
+    }
+
+    /// Unresolved sequence positions after the last observed group
+    pub fn c_terminal_truncation(&self) -> Range<usize> {
+        let groups = self.sequence_groups();
+        let end = groups.len();
+        groups
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(end, |last| last + 1)..end
+    }
+
+    /// Unresolved sequence positions between observed groups
+    pub fn internal_gaps(&self) -> Vec<Range<usize>> {
+        let (n_terminal, c_terminal) = (self.n_terminal_truncation(), self.c_terminal_truncation());
+        self.missing_ranges()
+            .into_iter()
+            .filter(|range| *range != n_terminal && *range != c_terminal)
+            .collect()
+    }
+
+    /// Entity sequence with unresolved positions replaced by [`GAP`](constant.GAP.html)
+    pub fn observed_sequence(&self) -> Option<String> {
+        let sequence = self.sequence()?;
+        Some(
+            sequence
+                .chars()
+                .zip(self.sequence_groups())
+                .map(|(code, group)| if group.is_some() { code } else { GAP })
+                .collect(),
+        )
+    }
+}
+
+impl Mmtf {
+    /// View on the chain with the given index
+    pub fn chain(&self, index: usize) -> Chain<'_> {
+        Chain::new(self, index)
+    }
+
+    /// Views on all chains
+    pub fn chains(&self) -> Vec<Chain<'_>> {
+        (0..self.groups_per_chain.len())
+            .map(|index| self.chain(index))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_support::load_173d;
+
+    #[test]
+    fn it_map_complete_chain() {
+        let mmtf = load_173d();
+        let chain = mmtf.chain(1);
+
+        assert_eq!(8..16, chain.groups());
+        assert_eq!(Some("B"), chain.name());
+        assert_eq!(Some("GAAGCTTC"), chain.sequence());
+        assert_eq!(Some("GAAGCTTC".to_string()), chain.observed_sequence());
+        assert_eq!(
+            (8..16).map(Some).collect::<Vec<_>>(),
+            chain.sequence_groups()
+        );
+        assert!(chain.missing_ranges().is_empty());
+        assert_eq!(0..0, chain.n_terminal_truncation());
+        assert_eq!(8..8, chain.c_terminal_truncation());
+    }
+
+    #[test]
+    fn it_report_truncations() {
+        let mmtf = load_173d();
+
+        let chain = mmtf.chain(2);
+        assert_eq!(Some("TXPGVX-----".to_string()), chain.observed_sequence());
+        assert_eq!(vec![6..11], chain.missing_ranges());
+        assert_eq!(0..0, chain.n_terminal_truncation());
+        assert_eq!(6..11, chain.c_terminal_truncation());
+        assert!(chain.internal_gaps().is_empty());
+
+        let chain = mmtf.chain(3);
+        assert_eq!(vec![0..5], chain.missing_ranges());
+        assert_eq!(0..5, chain.n_terminal_truncation());
+        assert_eq!(11..11, chain.c_terminal_truncation());
+    }
+
+    #[test]
+    fn it_report_internal_gaps() {
+        let mmtf = load_173d();
+        let atoms = mmtf.select_atoms("chain A and not resi 3-4").unwrap();
+        let mmtf = mmtf.select(&atoms);
+
+        let chain = mmtf.chain(0);
+        assert_eq!(Some("GA--CTTC".to_string()), chain.observed_sequence());
+        assert_eq!(vec![2..4], chain.internal_gaps());
+        assert_eq!(vec![2..4], chain.missing_ranges());
+    }
+
+    #[test]
+    fn it_skip_chains_without_entity_sequence() {
+        let mmtf = load_173d();
+        let waters = mmtf.chain(4);
+        assert_eq!(Some(""), waters.sequence());
+        assert!(waters.sequence_groups().is_empty());
+        assert_eq!(8, mmtf.chains().len());
+    }
+}
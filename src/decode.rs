@@ -1,7 +1,7 @@
-use std::io::{Cursor, Error, Read};
+use std::fmt;
+use std::io::{Cursor, Error, ErrorKind};
 use byteorder::{BigEndian, ReadBytesExt};
-use serde::de::Deserializer;
-use serde_bytes;
+use serde::de::{self, DeserializeSeed, Deserializer, SeqAccess, Visitor};
 
 use super::encode::{EncodeError, Header, HeaderLayout, Strategy, StrategyDataTypes};
 use super::encoding::{IntegerEncoding, RecursiveIndexing, RunLength};
@@ -19,12 +19,12 @@ impl<'a> Decoder<'a> {
         Decoder { reader }
     }
 
-    fn field(&mut self) -> Result<Vec<u8>, Error> {
-        // Skip header bytes
-        self.reader.set_position(12);
-        let mut buffer: Vec<u8> = Vec::new();
-        self.reader.read_to_end(&mut buffer)?;
-        Ok(buffer)
+    fn field(&self) -> Result<&'a [u8], Error> {
+        // Skip header bytes, borrowing the data instead of copying it
+        let bytes: &'a [u8] = self.reader.get_ref();
+        bytes
+            .get(12..)
+            .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "missing header bytes"))
     }
 }
 
@@ -59,24 +59,24 @@ impl<'a> Strategy for Decoder<'a> {
 
         match header.codec {
             1 => {
-                let decoded: Vec<f32> = binary_decoder::Interpret::from(&field[..])?;
+                let decoded: Vec<f32> = binary_decoder::Interpret::from(field)?;
                 Ok(StrategyDataTypes::VecFloat32(decoded))
             }
             2 => Ok(StrategyDataTypes::VecInt8(
-                binary_decoder::Interpret::from(&field[..])?,
+                binary_decoder::Interpret::from(field)?,
             )),
             3 => Ok(StrategyDataTypes::VecInt16(
-                binary_decoder::Interpret::from(&field[..])?,
+                binary_decoder::Interpret::from(field)?,
             )),
             4 => Ok(StrategyDataTypes::VecInt32(
-                binary_decoder::Interpret::from(&field[..])?,
+                binary_decoder::Interpret::from(field)?,
             )),
             5 => {
-                let result: Vec<String> = binary_decoder::Interpret::from(&field[..])?;
+                let result: Vec<String> = binary_decoder::Interpret::from(field)?;
                 Ok(StrategyDataTypes::VecString(result))
             }
             6 => {
-                let data: Vec<i32> = binary_decoder::Interpret::from(&field[..])?;
+                let data: Vec<i32> = binary_decoder::Interpret::from(field)?;
                 RunLength::decode(&data)
                     .and_then(|v| {
                         let r: Vec<char> = binary_decoder::Interpret::from(&v[..])?;
@@ -85,40 +85,40 @@ impl<'a> Strategy for Decoder<'a> {
                     .and_then(|v| Ok(StrategyDataTypes::VecChar(v)))
             }
             7 => {
-                let data: Vec<i32> = binary_decoder::Interpret::from(&field[..])?;
+                let data: Vec<i32> = binary_decoder::Interpret::from(field)?;
                 RunLength::decode(&data).and_then(|v| Ok(StrategyDataTypes::VecInt32(v)))
             }
-            8 => DeltaRunlength::decode(&field).and_then(|v| Ok(StrategyDataTypes::VecInt32(v))),
-            9 => IntegerRunLength::decode(&field, header.parameter)
+            8 => DeltaRunlength::decode(field).and_then(|v| Ok(StrategyDataTypes::VecInt32(v))),
+            9 => IntegerRunLength::decode(field, header.parameter)
                 .and_then(|v| Ok(StrategyDataTypes::VecFloat32(v))),
-            10 => IntegerDeltaRecursive::decode(&field[..], header.parameter)
+            10 => IntegerDeltaRecursive::decode(field, header.parameter)
                 .and_then(|v| Ok(StrategyDataTypes::VecFloat32(v))),
             11 => {
-                let r: Vec<i16> = binary_decoder::Interpret::from(&field[..])?;
+                let r: Vec<i16> = binary_decoder::Interpret::from(field)?;
                 IntegerEncoding::decode(&r, header.parameter)
                     .and_then(|v| Ok(StrategyDataTypes::VecFloat32(v)))
             }
             12 => {
-                let data: Vec<i16> = binary_decoder::Interpret::from(&field[..])?;
+                let data: Vec<i16> = binary_decoder::Interpret::from(field)?;
                 let res: Vec<f32> = RecursiveIndexing::decode(&data)
                     .and_then(|v| IntegerEncoding::decode(&v, header.parameter))
                     .and_then(Ok)?;
                 Ok(StrategyDataTypes::VecFloat32(res))
             }
             13 => {
-                let data: Vec<i8> = binary_decoder::Interpret::from(&field[..])?;
+                let data: Vec<i8> = binary_decoder::Interpret::from(field)?;
                 let res: Vec<f32> = RecursiveIndexing::decode(&data[..])
                     .and_then(|v| IntegerEncoding::decode(&v, header.parameter))
                     .and_then(Ok)?;
                 Ok(StrategyDataTypes::VecFloat32(res))
             }
             14 => {
-                let data: Vec<i16> = binary_decoder::Interpret::from(&field[..])?;
+                let data: Vec<i16> = binary_decoder::Interpret::from(field)?;
                 let res: Vec<i32> = RecursiveIndexing::decode(&data[..])?;
                 Ok(StrategyDataTypes::VecInt32(res))
             }
             15 => {
-                let data: Vec<i8> = binary_decoder::Interpret::from(&field[..])?;
+                let data: Vec<i8> = binary_decoder::Interpret::from(field)?;
                 let res: Vec<i32> = RecursiveIndexing::decode(&data[..])?;
                 Ok(StrategyDataTypes::VecInt32(res))
             }
@@ -136,11 +136,76 @@ where
     T: From<StrategyDataTypes>,
     D: Deserializer<'de>,
 {
-    let re: Vec<u8> = serde_bytes::deserialize(deserialize)?;
-    let decoded = Decoder::new(&re).apply().unwrap();
+    let decoded = deserialize.deserialize_bytes(EncodedVisitor)?;
     Ok(From::from(decoded))
 }
 
+/// Decodes a binary encoded field directly from the bytes
+/// handed out by the deserializer
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EncodedVisitor;
+
+impl<'de> Visitor<'de> for EncodedVisitor {
+    type Value = StrategyDataTypes;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("binary encoded MMTF field")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Decoder::new(v).apply().map_err(E::custom)
+    }
+
+    /// Encoded fields written as array of bytes instead of binary data
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.visit_bytes(&collect_bytes(seq)?)
+    }
+}
+
+fn collect_bytes<'de, A: SeqAccess<'de>>(mut seq: A) -> Result<Vec<u8>, A::Error> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+    while let Some(byte) = seq.next_element()? {
+        bytes.push(byte);
+    }
+    Ok(bytes)
+}
+
+impl<'de> DeserializeSeed<'de> for EncodedVisitor {
+    type Value = StrategyDataTypes;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_bytes(self)
+    }
+}
+
+/// Reads only the header of a binary encoded field
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HeaderVisitor;
+
+impl<'de> Visitor<'de> for HeaderVisitor {
+    type Value = HeaderLayout;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("binary encoded MMTF field")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Decoder::new(v).header().map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.visit_bytes(&collect_bytes(seq)?)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for HeaderVisitor {
+    type Value = HeaderLayout;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_bytes(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,6 +265,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_decode_encoded_bytes_and_arrays() {
+        use rmps::Deserializer;
+
+        let field = [0, 0, 0, 4, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 7];
+        let as_bin: Vec<u8> = [0xc4, 16].iter().chain(field.iter()).cloned().collect();
+        let as_array: Vec<u8> = [0xdc, 0, 16].iter().chain(field.iter()).cloned().collect();
+
+        for data in &[as_bin, as_array] {
+            let mut de = Deserializer::new(&data[..]);
+            match EncodedVisitor.deserialize(&mut de).unwrap() {
+                StrategyDataTypes::VecInt32(actual) => assert_eq!(vec![7], actual),
+                _ => panic!(),
+            }
+        }
+    }
+
     #[test]
     fn test_apply_strategy_for_type_1() {
         let data = [
@@ -0,0 +1,523 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+
+use hierarchy::Hierarchy;
+use mmtf::Mmtf;
+
+/// Identifies a group across versions of a structure
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct GroupKey {
+    /// Index of the model
+    pub model: usize,
+
+    /// Chain id, see [`Mmtf.chain_id_list`](../mmtf/struct.Mmtf.html#structfield.chain_id_list)
+    pub chain_id: String,
+
+    /// Group id, see [`Mmtf.group_id_list`](../mmtf/struct.Mmtf.html#structfield.group_id_list)
+    pub group_id: i32,
+
+    /// Insertion code, if any
+    pub ins_code: Option<char>,
+}
+
+/// Identifies an atom across versions of a structure
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct AtomKey {
+    /// Group of the atom
+    pub group: GroupKey,
+
+    /// Atom name
+    pub atom_name: String,
+
+    /// Alternate location label, if any
+    pub alt_loc: Option<char>,
+}
+
+/// A changed metadata field, values formatted as strings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MetadataChange {
+    /// Name of the [`Mmtf`](../mmtf/struct.Mmtf.html) field
+    pub field: String,
+
+    /// Value in the first structure
+    pub old: Option<String>,
+
+    /// Value in the second structure
+    pub new: Option<String>,
+}
+
+/// A difference in the chains, groups or atoms of two structures
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum TopologyChange {
+    /// Chain only present in the second structure
+    ChainAdded {
+        /// Index of the model
+        model: usize,
+        /// Chain id
+        chain_id: String,
+    },
+
+    /// Chain only present in the first structure
+    ChainRemoved {
+        /// Index of the model
+        model: usize,
+        /// Chain id
+        chain_id: String,
+    },
+
+    /// Group only present in the second structure
+    GroupAdded {
+        /// Key of the group
+        group: GroupKey,
+        /// Group name
+        name: String,
+    },
+
+    /// Group only present in the first structure
+    GroupRemoved {
+        /// Key of the group
+        group: GroupKey,
+        /// Group name
+        name: String,
+    },
+
+    /// Group of a different chemical component
+    GroupRenamed {
+        /// Key of the group
+        group: GroupKey,
+        /// Group name in the first structure
+        old_name: String,
+        /// Group name in the second structure
+        new_name: String,
+    },
+
+    /// Atom only present in the second structure
+    AtomAdded(AtomKey),
+
+    /// Atom only present in the first structure
+    AtomRemoved(AtomKey),
+
+    /// Atom at the same position of its group with a different name
+    AtomRenamed {
+        /// Key of the atom in the first structure
+        atom: AtomKey,
+        /// Atom name in the second structure
+        new_name: String,
+    },
+}
+
+/// Displacement of an atom present in both structures
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CoordinateDeviation {
+    /// Key of the atom
+    pub atom: AtomKey,
+
+    /// Distance between the two positions in **Å**
+    pub distance: f32,
+}
+
+/// Differences between two structures, see [`diff`](fn.diff.html)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Diff {
+    /// Changed metadata fields
+    pub metadata: Vec<MetadataChange>,
+
+    /// Added, removed and renamed chains, groups and atoms
+    pub topology: Vec<TopologyChange>,
+
+    /// Atoms which moved farther than the tolerance
+    pub coordinates: Vec<CoordinateDeviation>,
+}
+
+impl Diff {
+    /// Whether the structures are equal up to the tolerance
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty() && self.topology.is_empty() && self.coordinates.is_empty()
+    }
+}
+
+/// Settings of the structure comparison
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Largest displacement in **Å** of an atom that is not reported
+    pub tolerance: f32,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions { tolerance: 0.01 }
+    }
+}
+
+/// Label of an optional character field, `'\0'` and `' '` meaning none
+fn label(list: &Option<Vec<char>>, index: usize) -> Option<char> {
+    list.as_ref()
+        .map(|list| list[index])
+        .filter(|c| *c != '\0' && *c != ' ')
+}
+
+/// Groups of each key, in structure order
+fn group_keys(mmtf: &Mmtf, hierarchy: &Hierarchy) -> BTreeMap<GroupKey, Vec<usize>> {
+    let mut keys: BTreeMap<GroupKey, Vec<usize>> = BTreeMap::new();
+    for group in 0..hierarchy.num_groups() {
+        let chain = hierarchy.group_chain[group];
+        let key = GroupKey {
+            model: hierarchy.chain_model[chain],
+            chain_id: mmtf.chain_id_list[chain].clone(),
+            group_id: mmtf.group_id_list[group],
+            ins_code: label(&mmtf.ins_code_list, group),
+        };
+        keys.entry(key).or_default().push(group);
+    }
+    keys
+}
+
+/// Chain ids of each model
+fn chain_keys(mmtf: &Mmtf, hierarchy: &Hierarchy) -> BTreeSet<(usize, String)> {
+    (0..hierarchy.num_chains())
+        .map(|chain| {
+            (
+                hierarchy.chain_model[chain],
+                mmtf.chain_id_list[chain].clone(),
+            )
+        })
+        .collect()
+}
+
+fn group_name(mmtf: &Mmtf, group: usize) -> &str {
+    &mmtf.group_list[mmtf.group_type_list[group] as usize].group_name
+}
+
+fn atom_names(mmtf: &Mmtf, group: usize) -> &[String] {
+    &mmtf.group_list[mmtf.group_type_list[group] as usize].atom_name_list
+}
+
+impl DiffOptions {
+    /// Compare the structure `a` with its new version `b`
+    pub fn compare(&self, a: &Mmtf, b: &Mmtf) -> Diff {
+        let mut diff = Diff {
+            metadata: metadata_changes(a, b),
+            ..Default::default()
+        };
+
+        let (hierarchy_a, hierarchy_b) = (a.hierarchy(), b.hierarchy());
+        let (chains_a, chains_b) = (chain_keys(a, &hierarchy_a), chain_keys(b, &hierarchy_b));
+        for (model, chain_id) in chains_a.difference(&chains_b) {
+            diff.topology.push(TopologyChange::ChainRemoved {
+                model: *model,
+                chain_id: chain_id.clone(),
+            });
+        }
+        for (model, chain_id) in chains_b.difference(&chains_a) {
+            diff.topology.push(TopologyChange::ChainAdded {
+                model: *model,
+                chain_id: chain_id.clone(),
+            });
+        }
+
+        let (mut groups_a, mut groups_b) =
+            (group_keys(a, &hierarchy_a), group_keys(b, &hierarchy_b));
+        let keys: BTreeSet<GroupKey> = groups_a.keys().chain(groups_b.keys()).cloned().collect();
+        for key in keys {
+            let list_a = groups_a.remove(&key).unwrap_or_default();
+            let list_b = groups_b.remove(&key).unwrap_or_default();
+            for &group in list_a.iter().skip(list_b.len()) {
+                diff.topology.push(TopologyChange::GroupRemoved {
+                    group: key.clone(),
+                    name: group_name(a, group).to_string(),
+                });
+            }
+            for &group in list_b.iter().skip(list_a.len()) {
+                diff.topology.push(TopologyChange::GroupAdded {
+                    group: key.clone(),
+                    name: group_name(b, group).to_string(),
+                });
+            }
+            for (&group_a, &group_b) in list_a.iter().zip(&list_b) {
+                let (name_a, name_b) = (group_name(a, group_a), group_name(b, group_b));
+                if name_a != name_b {
+                    diff.topology.push(TopologyChange::GroupRenamed {
+                        group: key.clone(),
+                        old_name: name_a.to_string(),
+                        new_name: name_b.to_string(),
+                    });
+                }
+                self.compare_atoms(
+                    a,
+                    b,
+                    (&hierarchy_a, &hierarchy_b),
+                    (group_a, group_b),
+                    &key,
+                    &mut diff,
+                );
+            }
+        }
+        diff
+    }
+
+    /// Match the atoms of two corresponding groups by name and alternate location
+    fn compare_atoms(
+        &self,
+        a: &Mmtf,
+        b: &Mmtf,
+        hierarchies: (&Hierarchy, &Hierarchy),
+        groups: (usize, usize),
+        key: &GroupKey,
+        diff: &mut Diff,
+    ) {
+        let start_a = hierarchies.0.group_atoms[groups.0].start;
+        let start_b = hierarchies.1.group_atoms[groups.1].start;
+        let (names_a, names_b) = (atom_names(a, groups.0), atom_names(b, groups.1));
+        let atom_key = |name: &str, alt_loc: Option<char>| AtomKey {
+            group: key.clone(),
+            atom_name: name.to_string(),
+            alt_loc,
+        };
+
+        let mut unmatched_b: BTreeMap<(&str, Option<char>), usize> = BTreeMap::new();
+        for (index, name) in names_b.iter().enumerate() {
+            let alt_loc = label(&b.alt_loc_list, start_b + index);
+            unmatched_b.entry((name.as_str(), alt_loc)).or_insert(index);
+        }
+        let mut unmatched_a = Vec::new();
+        for (index, name) in names_a.iter().enumerate() {
+            let alt_loc = label(&a.alt_loc_list, start_a + index);
+            match unmatched_b.remove(&(name.as_str(), alt_loc)) {
+                Some(other) => {
+                    let (p, q) = (a.position(start_a + index), b.position(start_b + other));
+                    let distance =
+                        ((p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2))
+                            .sqrt();
+                    if distance > self.tolerance {
+                        diff.coordinates.push(CoordinateDeviation {
+                            atom: atom_key(name, alt_loc),
+                            distance,
+                        });
+                    }
+                }
+                None => unmatched_a.push((index, alt_loc)),
+            }
+        }
+
+        // atoms left over at the same position of the group were renamed
+        let mut added: BTreeMap<usize, (&str, Option<char>)> = unmatched_b
+            .into_iter()
+            .map(|(name, index)| (index, name))
+            .collect();
+        for (index, alt_loc) in unmatched_a {
+            let atom = atom_key(&names_a[index], alt_loc);
+            match added.remove(&index) {
+                Some((new_name, new_alt_loc)) if new_alt_loc == alt_loc => {
+                    diff.topology.push(TopologyChange::AtomRenamed {
+                        atom,
+                        new_name: new_name.to_string(),
+                    })
+                }
+                Some(other) => {
+                    diff.topology.push(TopologyChange::AtomRemoved(atom));
+                    added.insert(index, other);
+                }
+                None => diff.topology.push(TopologyChange::AtomRemoved(atom)),
+            }
+        }
+        for (_, (name, alt_loc)) in added {
+            diff.topology
+                .push(TopologyChange::AtomAdded(atom_key(name, alt_loc)));
+        }
+    }
+}
+
+/// Changed metadata fields of the two structures
+fn metadata_changes(a: &Mmtf, b: &Mmtf) -> Vec<MetadataChange> {
+    fn value<T: Display>(value: &Option<T>) -> Option<String> {
+        value.as_ref().map(|value| value.to_string())
+    }
+    fn list<T: Display>(values: &Option<Vec<T>>, separator: &str) -> Option<String> {
+        values.as_ref().map(|values| {
+            values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(separator)
+        })
+    }
+
+    let fields = vec![
+        ("title", value(&a.title), value(&b.title)),
+        (
+            "structure_id",
+            value(&a.structure_id),
+            value(&b.structure_id),
+        ),
+        (
+            "deposition_date",
+            value(&a.deposition_date),
+            value(&b.deposition_date),
+        ),
+        (
+            "release_date",
+            value(&a.release_date),
+            value(&b.release_date),
+        ),
+        ("resolution", value(&a.resolution), value(&b.resolution)),
+        ("r_free", value(&a.r_free), value(&b.r_free)),
+        ("r_work", value(&a.r_work), value(&b.r_work)),
+        ("space_group", value(&a.space_group), value(&b.space_group)),
+        (
+            "unit_cell",
+            list(&a.unit_cell, " "),
+            list(&b.unit_cell, " "),
+        ),
+        (
+            "experimental_methods",
+            list(&a.experimental_methods, ", "),
+            list(&b.experimental_methods, ", "),
+        ),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| MetadataChange {
+            field: field.to_string(),
+            old,
+            new,
+        })
+        .collect()
+}
+
+/// Compare the structure `a` with its new version `b` using the default
+/// [`DiffOptions`](struct.DiffOptions.html)
+///
+/// Chains are matched by model and chain id, groups additionally by group id
+/// and insertion code, and atoms by name and alternate location. Atoms of a
+/// group without a match in the other version, but at the same position in
+/// their group, are reported as renamed.
+///
+/// # Examples
+///
+/// ```
+/// # use std::path::Path;
+/// # use std::env;
+/// use std::fs::File;
+/// use mmtf::Mmtf;
+/// use mmtf::diff::TopologyChange;
+///
+/// # let file_path = Path::new(&env::current_dir().unwrap())
+/// #                                    .join("tests")
+/// #                                    .join("data")
+/// #                                    .join("173D.mmtf");
+/// let old = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+/// let new = old.select(&old.select_atoms("not water").unwrap());
+///
+/// let diff = mmtf::diff(&old, &new);
+/// assert!(diff.metadata.is_empty());
+/// assert!(diff.topology.contains(&TopologyChange::ChainRemoved {
+///     model: 0,
+///     chain_id: "E".to_string(),
+/// }));
+/// ```
+pub fn diff(a: &Mmtf, b: &Mmtf) -> Diff {
+    DiffOptions::default().compare(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    fn first_group_key() -> GroupKey {
+        GroupKey {
+            model: 0,
+            chain_id: "A".to_string(),
+            group_id: 1,
+            ins_code: None,
+        }
+    }
+
+    #[test]
+    fn it_find_no_differences_of_identical_structures() {
+        let mmtf = load_173d();
+        assert!(diff(&mmtf, &mmtf.clone()).is_empty());
+    }
+
+    #[test]
+    fn it_report_metadata_changes() {
+        let old = load_173d();
+        let mut new = old.clone();
+        new.title = Some("A new title".to_string());
+        new.r_free = Some(0.25);
+
+        let changes = diff(&old, &new).metadata;
+        let fields: Vec<&str> = changes.iter().map(|change| change.field.as_str()).collect();
+        assert_eq!(vec!["title", "r_free"], fields);
+        assert_eq!(Some("A new title".to_string()), changes[0].new);
+        assert_eq!(old.r_free.map(|r| r.to_string()), changes[1].old);
+        assert_eq!(Some("0.25".to_string()), changes[1].new);
+    }
+
+    #[test]
+    fn it_report_coordinate_deviations() {
+        let old = load_173d();
+        let mut new = old.clone();
+        new.x_coord_list[0] += 0.5;
+        new.y_coord_list[1] += 0.005;
+
+        let options = DiffOptions::default();
+        let deviations = options.compare(&old, &new).coordinates;
+        assert_eq!(1, deviations.len());
+        assert_eq!(first_group_key(), deviations[0].atom.group);
+        assert!((deviations[0].distance - 0.5).abs() < 1e-4);
+
+        let strict = DiffOptions { tolerance: 0.001 };
+        assert_eq!(2, strict.compare(&old, &new).coordinates.len());
+    }
+
+    #[test]
+    fn it_report_topology_changes() {
+        let old = load_173d();
+        let mut new = old.clone();
+        let group_type = new.group_type_list[0] as usize;
+        let mut renamed = new.group_list[group_type].clone();
+        let first_name = renamed.atom_name_list[0].clone();
+        renamed.group_name = "XYZ".to_string();
+        renamed.atom_name_list[0] = "NEW".to_string();
+        new.group_list.push(renamed);
+        new.group_type_list[0] = new.group_list.len() as i32 - 1;
+
+        let topology = diff(&old, &new).topology;
+        assert_eq!(2, topology.len());
+        assert_eq!(
+            TopologyChange::GroupRenamed {
+                group: first_group_key(),
+                old_name: "DG".to_string(),
+                new_name: "XYZ".to_string(),
+            },
+            topology[0]
+        );
+        assert_eq!(
+            TopologyChange::AtomRenamed {
+                atom: AtomKey {
+                    group: first_group_key(),
+                    atom_name: first_name,
+                    alt_loc: None,
+                },
+                new_name: "NEW".to_string(),
+            },
+            topology[1]
+        );
+
+        // removing the waters of chain E removes the chain and its groups
+        let dry = old.select(&old.select_atoms("not water").unwrap());
+        let removed = diff(&old, &dry).topology;
+        assert!(removed.iter().all(|change| matches!(
+            change,
+            TopologyChange::ChainRemoved { .. } | TopologyChange::GroupRemoved { .. }
+        )));
+        let reverse = diff(&dry, &old).topology;
+        assert_eq!(removed.len(), reverse.len());
+        assert!(reverse.contains(&TopologyChange::ChainAdded {
+            model: 0,
+            chain_id: "E".to_string(),
+        }));
+    }
+}
@@ -0,0 +1,306 @@
+use hierarchy::Hierarchy;
+use mmtf::Mmtf;
+
+/// Largest C–N distance in **Å** of consecutive residues still considered a peptide bond
+pub const MAX_PEPTIDE_BOND: f32 = 2.0;
+
+/// Atoms defining the side-chain dihedral angles chi1 to chi5 of the standard amino acids
+#[rustfmt::skip]
+pub fn chi_atoms(group_name: &str) -> &'static [[&'static str; 4]] {
+    match group_name.to_ascii_uppercase().as_str() {
+        "ARG" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD"], ["CB", "CG", "CD", "NE"],
+                   ["CG", "CD", "NE", "CZ"], ["CD", "NE", "CZ", "NH1"]],
+        "ASN" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "OD1"]],
+        "ASP" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "OD1"]],
+        "CYS" => &[["N", "CA", "CB", "SG"]],
+        "GLN" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD"], ["CB", "CG", "CD", "OE1"]],
+        "GLU" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD"], ["CB", "CG", "CD", "OE1"]],
+        "HIS" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "ND1"]],
+        "ILE" => &[["N", "CA", "CB", "CG1"], ["CA", "CB", "CG1", "CD1"]],
+        "LEU" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD1"]],
+        "LYS" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD"], ["CB", "CG", "CD", "CE"],
+                   ["CG", "CD", "CE", "NZ"]],
+        "MET" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "SD"], ["CB", "CG", "SD", "CE"]],
+        "PHE" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD1"]],
+        "PRO" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD"]],
+        "SER" => &[["N", "CA", "CB", "OG"]],
+        "THR" => &[["N", "CA", "CB", "OG1"]],
+        "TRP" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD1"]],
+        "TYR" => &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD1"]],
+        "VAL" => &[["N", "CA", "CB", "CG1"]],
+        _ => &[],
+    }
+}
+
+/// Dihedral angle in degrees, in the range `-180..=180`, of the four positions
+///
+/// # Examples
+///
+/// ```
+/// use mmtf::dihedrals::dihedral;
+///
+/// let angle = dihedral([1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 1.0]);
+/// assert!((angle - 90.0).abs() < 1e-4);
+/// ```
+pub fn dihedral(a: [f32; 3], b: [f32; 3], c: [f32; 3], d: [f32; 3]) -> f32 {
+    let sub = |p: [f32; 3], q: [f32; 3]| [p[0] - q[0], p[1] - q[1], p[2] - q[2]];
+    let cross = |p: [f32; 3], q: [f32; 3]| {
+        [
+            p[1] * q[2] - p[2] * q[1],
+            p[2] * q[0] - p[0] * q[2],
+            p[0] * q[1] - p[1] * q[0],
+        ]
+    };
+    let dot = |p: [f32; 3], q: [f32; 3]| p[0] * q[0] + p[1] * q[1] + p[2] * q[2];
+
+    let (b1, b2, b3) = (sub(b, a), sub(c, b), sub(d, c));
+    let (n1, n2) = (cross(b1, b2), cross(b2, b3));
+    let y = dot(b2, b2).sqrt() * dot(b1, n2);
+    y.atan2(dot(n1, n2)).to_degrees()
+}
+
+/// Backbone and side-chain dihedral angles of a group, in degrees
+///
+/// Angles are `None` if the group is no amino acid, an atom is missing or
+/// the neighboring residue is not connected by a peptide bond.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dihedrals {
+    /// C(i-1), N, CA, C
+    pub phi: Option<f32>,
+
+    /// N, CA, C, N(i+1)
+    pub psi: Option<f32>,
+
+    /// CA, C, N(i+1), CA(i+1)
+    pub omega: Option<f32>,
+
+    /// Side-chain angles chi1 to chi5, as many as the residue type defines,
+    /// see [`chi_atoms`](fn.chi_atoms.html)
+    pub chi: Vec<Option<f32>>,
+}
+
+/// Atom lookup by name, resolving alternate locations
+struct Atoms<'a> {
+    mmtf: &'a Mmtf,
+    hierarchy: Hierarchy,
+    /// Alternate location used for each group, the lowest label of its atoms
+    alt_locs: Vec<Option<char>>,
+}
+
+impl<'a> Atoms<'a> {
+    fn new(mmtf: &'a Mmtf) -> Self {
+        let hierarchy = mmtf.hierarchy();
+        let alt_locs = hierarchy
+            .group_atoms
+            .iter()
+            .map(|atoms| atoms.clone().filter_map(|atom| mmtf.alt_loc(atom)).min())
+            .collect();
+        Atoms {
+            mmtf,
+            hierarchy,
+            alt_locs,
+        }
+    }
+
+    /// Atom of the group with the given name. All atoms of a group are taken
+    /// from the same alternate location, atoms without label are shared by
+    /// all of them.
+    fn find(&self, group: usize, name: &str) -> Option<[f32; 3]> {
+        let group_type = &self.mmtf.group_list[self.mmtf.group_type_list[group] as usize];
+        let start = self.hierarchy.group_atoms[group].start;
+        let alt_loc = self.alt_locs[group];
+        group_type
+            .atom_name_list
+            .iter()
+            .enumerate()
+            .filter(|&(_, atom_name)| atom_name == name)
+            .map(|(i, _)| start + i)
+            .find(|atom| {
+                let label = self.mmtf.alt_loc(*atom);
+                label.is_none() || label == alt_loc
+            })
+            .map(|atom| self.mmtf.position(atom))
+    }
+
+    fn dihedral(&self, atoms: [(usize, &str); 4]) -> Option<f32> {
+        Some(dihedral(
+            self.find(atoms[0].0, atoms[0].1)?,
+            self.find(atoms[1].0, atoms[1].1)?,
+            self.find(atoms[2].0, atoms[2].1)?,
+            self.find(atoms[3].0, atoms[3].1)?,
+        ))
+    }
+
+    /// Whether the C atom of `previous` is bonded to the N atom of `next`
+    fn peptide_bond(&self, previous: usize, next: usize) -> bool {
+        match (self.find(previous, "C"), self.find(next, "N")) {
+            (Some(c), Some(n)) => {
+                let distance =
+                    ((c[0] - n[0]).powi(2) + (c[1] - n[1]).powi(2) + (c[2] - n[2]).powi(2)).sqrt();
+                distance <= MAX_PEPTIDE_BOND
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Mmtf {
+    /// Backbone and side-chain dihedral angles of each group
+    ///
+    /// Neighboring residues are taken from the [residues](#method.chain_residues)
+    /// of the chain, so variants of micro-heterogeneous residues are not
+    /// treated as neighbors of each other.
+    pub fn dihedrals(&self) -> Vec<Dihedrals> {
+        let atoms = Atoms::new(self);
+        let mut dihedrals = vec![Dihedrals::default(); self.group_type_list.len()];
+
+        for chain in 0..self.groups_per_chain.len() {
+            let groups = atoms.hierarchy.chain_groups[chain].clone();
+            let residues = self.group_residues(chain, groups);
+            for (index, residue) in residues.iter().enumerate() {
+                let previous = index
+                    .checked_sub(1)
+                    .map(|i| residues[i].groups[0])
+                    .filter(|previous| self.is_amino_acid_group(*previous));
+                let next = residues
+                    .get(index + 1)
+                    .map(|next| next.groups[0])
+                    .filter(|next| self.is_amino_acid_group(*next));
+
+                for &group in residue.groups.iter() {
+                    if !self.is_amino_acid_group(group) {
+                        continue;
+                    }
+                    let angles = &mut dihedrals[group];
+                    if let Some(previous) = previous.filter(|p| atoms.peptide_bond(*p, group)) {
+                        angles.phi = atoms.dihedral([
+                            (previous, "C"),
+                            (group, "N"),
+                            (group, "CA"),
+                            (group, "C"),
+                        ]);
+                    }
+                    if let Some(next) = next.filter(|n| atoms.peptide_bond(group, *n)) {
+                        angles.psi = atoms.dihedral([
+                            (group, "N"),
+                            (group, "CA"),
+                            (group, "C"),
+                            (next, "N"),
+                        ]);
+                        angles.omega = atoms.dihedral([
+                            (group, "CA"),
+                            (group, "C"),
+                            (next, "N"),
+                            (next, "CA"),
+                        ]);
+                    }
+
+                    let group_name =
+                        &self.group_list[self.group_type_list[group] as usize].group_name;
+                    angles.chi = chi_atoms(group_name)
+                        .iter()
+                        .map(|names| {
+                            atoms.dihedral([
+                                (group, names[0]),
+                                (group, names[1]),
+                                (group, names[2]),
+                                (group, names[3]),
+                            ])
+                        })
+                        .collect();
+                }
+            }
+        }
+        dihedrals
+    }
+
+    fn is_amino_acid_group(&self, group: usize) -> bool {
+        self.group_list[self.group_type_list[group] as usize].is_amino_acid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    #[test]
+    fn test_dihedral_sign_and_range() {
+        let (a, b, c) = ([1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 1.0]);
+        assert!((dihedral(a, b, c, [1.0, 0.0, 1.0])).abs() < 1e-4);
+        assert!((dihedral(a, b, c, [-1.0, 0.0, 1.0]).abs() - 180.0).abs() < 1e-4);
+        assert!((dihedral(a, b, c, [0.0, -1.0, 1.0]) + 90.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn it_compute_backbone_dihedrals() {
+        let mmtf = load_173d();
+        let dihedrals = mmtf.dihedrals();
+
+        // nucleotides and waters have no protein dihedrals
+        assert_eq!(Dihedrals::default(), dihedrals[0]);
+        assert_eq!(Dihedrals::default(), dihedrals[123]);
+
+        // the peptide of chain C starts with THR and is continued by the chromophore
+        let thr = &dihedrals[16];
+        assert_eq!(None, thr.phi);
+        assert!(thr.psi.is_some() && thr.omega.is_some());
+        assert_eq!(1, thr.chi.len());
+
+        // the proline ring restricts phi
+        let pro = &dihedrals[18];
+        assert!(pro.phi.is_some_and(|phi| phi > -100.0 && phi < -40.0));
+        assert!(pro
+            .chi
+            .iter()
+            .all(|chi| chi.is_some_and(|chi| chi.abs() < 45.0)));
+
+        // the bonds to the proline and the sarcosine of actinomycin are cis
+        let trans = |group: usize| dihedrals[group].omega.is_some_and(|o| o.abs() > 150.0);
+        let cis = |group: usize| dihedrals[group].omega.is_some_and(|o| o.abs() < 30.0);
+        assert!(trans(16));
+        assert!(cis(17));
+        assert!(cis(18));
+        assert!(trans(19));
+        assert_eq!(None, dihedrals[20].omega);
+    }
+
+    #[test]
+    fn it_use_one_alt_loc_per_group() {
+        let mut mmtf = load_173d();
+        let start = mmtf.hierarchy().group_atoms[16].start;
+        let names = &mmtf.group_list[mmtf.group_type_list[16] as usize].atom_name_list;
+        let position = |name: &str| start + names.iter().position(|n| n == name).unwrap();
+        let (cb, og1) = (position("CB"), position("OG1"));
+
+        let mut alt_locs = vec!['\0'; mmtf.x_coord_list.len()];
+        alt_locs[og1] = 'A';
+        mmtf.alt_loc_list = Some(alt_locs.clone());
+        assert!(mmtf.dihedrals()[16].chi[0].is_some());
+
+        // chi1 would mix the locations A and B
+        alt_locs[cb] = 'B';
+        mmtf.alt_loc_list = Some(alt_locs);
+        let dihedrals = mmtf.dihedrals();
+        assert_eq!(vec![None], dihedrals[16].chi);
+        assert!(dihedrals[16].psi.is_some());
+    }
+
+    #[test]
+    fn it_detect_chain_breaks() {
+        let mut mmtf = load_173d();
+        let pro_n = mmtf.hierarchy().group_atoms[18].start
+            + mmtf.group_list[mmtf.group_type_list[18] as usize]
+                .atom_name_list
+                .iter()
+                .position(|name| name == "N")
+                .unwrap();
+        mmtf.x_coord_list[pro_n] += 5.0;
+        let dihedrals = mmtf.dihedrals();
+
+        assert_eq!(None, dihedrals[18].phi);
+        assert_eq!(None, dihedrals[17].psi);
+        assert_eq!(None, dihedrals[17].omega);
+        assert!(dihedrals[18].psi.is_some());
+    }
+}
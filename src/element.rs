@@ -0,0 +1,421 @@
+use std::fmt;
+
+use mmtf::{GroupType, Mmtf};
+
+macro_rules! elements {
+    ($($variant:ident = $number:expr, $mass:expr, $covalent:expr, $vdw:expr, $en:expr;)*) => {
+        /// Chemical elements of the periodic table
+        ///
+        /// Each variant is named after the element symbol and its discriminant
+        /// is the atomic number.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum Element {
+            $($variant = $number,)*
+        }
+
+        /// All elements ordered by atomic number
+        const ELEMENTS: &[Element] = &[$(Element::$variant,)*];
+
+        impl Element {
+            /// The element symbol, e.g. `"Fe"`
+            pub fn symbol(&self) -> &'static str {
+                match *self {
+                    $(Element::$variant => stringify!($variant),)*
+                }
+            }
+
+            /// Standard atomic weight in **Da**. For elements without stable
+            /// isotopes this is the mass number of the longest-lived isotope.
+            pub fn mass(&self) -> f32 {
+                match *self {
+                    $(Element::$variant => $mass,)*
+                }
+            }
+
+            /// Single bond covalent radius in **Å**
+            /// (Cordero *et al.* 2008, Pyykkö & Atsumi 2009 beyond curium)
+            pub fn covalent_radius(&self) -> f32 {
+                match *self {
+                    $(Element::$variant => $covalent,)*
+                }
+            }
+
+            /// Van der Waals radius in **Å**
+            /// (Bondi 1964, Mantina *et al.* 2009), 2.0 **Å** when not tabulated
+            pub fn vdw_radius(&self) -> f32 {
+                match *self {
+                    $(Element::$variant => $vdw,)*
+                }
+            }
+
+            /// Pauling electronegativity, if defined
+            pub fn electronegativity(&self) -> Option<f32> {
+                match *self {
+                    $(Element::$variant => $en,)*
+                }
+            }
+        }
+    }
+}
+
+elements! {
+    H = 1, 1.008, 0.31, 1.20, Some(2.20);
+    He = 2, 4.0026, 0.28, 1.40, None;
+    Li = 3, 6.94, 1.28, 1.82, Some(0.98);
+    Be = 4, 9.0122, 0.96, 1.53, Some(1.57);
+    B = 5, 10.81, 0.84, 1.92, Some(2.04);
+    C = 6, 12.011, 0.76, 1.70, Some(2.55);
+    N = 7, 14.007, 0.71, 1.55, Some(3.04);
+    O = 8, 15.999, 0.66, 1.52, Some(3.44);
+    F = 9, 18.998, 0.57, 1.47, Some(3.98);
+    Ne = 10, 20.180, 0.58, 1.54, None;
+    Na = 11, 22.990, 1.66, 2.27, Some(0.93);
+    Mg = 12, 24.305, 1.41, 1.73, Some(1.31);
+    Al = 13, 26.982, 1.21, 1.84, Some(1.61);
+    Si = 14, 28.085, 1.11, 2.10, Some(1.90);
+    P = 15, 30.974, 1.07, 1.80, Some(2.19);
+    S = 16, 32.06, 1.05, 1.80, Some(2.58);
+    Cl = 17, 35.45, 1.02, 1.75, Some(3.16);
+    Ar = 18, 39.948, 1.06, 1.88, None;
+    K = 19, 39.098, 2.03, 2.75, Some(0.82);
+    Ca = 20, 40.078, 1.76, 2.31, Some(1.00);
+    Sc = 21, 44.956, 1.70, 2.00, Some(1.36);
+    Ti = 22, 47.867, 1.60, 2.00, Some(1.54);
+    V = 23, 50.942, 1.53, 2.00, Some(1.63);
+    Cr = 24, 51.996, 1.39, 2.00, Some(1.66);
+    Mn = 25, 54.938, 1.39, 2.00, Some(1.55);
+    Fe = 26, 55.845, 1.32, 2.00, Some(1.83);
+    Co = 27, 58.933, 1.26, 2.00, Some(1.88);
+    Ni = 28, 58.693, 1.24, 1.63, Some(1.91);
+    Cu = 29, 63.546, 1.32, 1.40, Some(1.90);
+    Zn = 30, 65.38, 1.22, 1.39, Some(1.65);
+    Ga = 31, 69.723, 1.22, 1.87, Some(1.81);
+    Ge = 32, 72.630, 1.20, 2.11, Some(2.01);
+    As = 33, 74.922, 1.19, 1.85, Some(2.18);
+    Se = 34, 78.971, 1.20, 1.90, Some(2.55);
+    Br = 35, 79.904, 1.20, 1.83, Some(2.96);
+    Kr = 36, 83.798, 1.16, 2.02, Some(3.00);
+    Rb = 37, 85.468, 2.20, 3.03, Some(0.82);
+    Sr = 38, 87.62, 1.95, 2.49, Some(0.95);
+    Y = 39, 88.906, 1.90, 2.00, Some(1.22);
+    Zr = 40, 91.224, 1.75, 2.00, Some(1.33);
+    Nb = 41, 92.906, 1.64, 2.00, Some(1.6);
+    Mo = 42, 95.95, 1.54, 2.00, Some(2.16);
+    Tc = 43, 98.0, 1.47, 2.00, Some(1.9);
+    Ru = 44, 101.07, 1.46, 2.00, Some(2.2);
+    Rh = 45, 102.91, 1.42, 2.00, Some(2.28);
+    Pd = 46, 106.42, 1.39, 1.63, Some(2.20);
+    Ag = 47, 107.87, 1.45, 1.72, Some(1.93);
+    Cd = 48, 112.41, 1.44, 1.58, Some(1.69);
+    In = 49, 114.82, 1.42, 1.93, Some(1.78);
+    Sn = 50, 118.71, 1.39, 2.17, Some(1.96);
+    Sb = 51, 121.76, 1.39, 2.06, Some(2.05);
+    Te = 52, 127.60, 1.38, 2.06, Some(2.1);
+    I = 53, 126.90, 1.39, 1.98, Some(2.66);
+    Xe = 54, 131.29, 1.40, 2.16, Some(2.6);
+    Cs = 55, 132.91, 2.44, 3.43, Some(0.79);
+    Ba = 56, 137.33, 2.15, 2.68, Some(0.89);
+    La = 57, 138.91, 2.07, 2.00, Some(1.10);
+    Ce = 58, 140.12, 2.04, 2.00, Some(1.12);
+    Pr = 59, 140.91, 2.03, 2.00, Some(1.13);
+    Nd = 60, 144.24, 2.01, 2.00, Some(1.14);
+    Pm = 61, 145.0, 1.99, 2.00, Some(1.13);
+    Sm = 62, 150.36, 1.98, 2.00, Some(1.17);
+    Eu = 63, 151.96, 1.98, 2.00, Some(1.2);
+    Gd = 64, 157.25, 1.96, 2.00, Some(1.20);
+    Tb = 65, 158.93, 1.94, 2.00, Some(1.1);
+    Dy = 66, 162.50, 1.92, 2.00, Some(1.22);
+    Ho = 67, 164.93, 1.92, 2.00, Some(1.23);
+    Er = 68, 167.26, 1.89, 2.00, Some(1.24);
+    Tm = 69, 168.93, 1.90, 2.00, Some(1.25);
+    Yb = 70, 173.05, 1.87, 2.00, Some(1.1);
+    Lu = 71, 174.97, 1.87, 2.00, Some(1.27);
+    Hf = 72, 178.49, 1.75, 2.00, Some(1.3);
+    Ta = 73, 180.95, 1.70, 2.00, Some(1.5);
+    W = 74, 183.84, 1.62, 2.00, Some(2.36);
+    Re = 75, 186.21, 1.51, 2.00, Some(1.9);
+    Os = 76, 190.23, 1.44, 2.00, Some(2.2);
+    Ir = 77, 192.22, 1.41, 2.00, Some(2.20);
+    Pt = 78, 195.08, 1.36, 1.75, Some(2.28);
+    Au = 79, 196.97, 1.36, 1.66, Some(2.54);
+    Hg = 80, 200.59, 1.32, 1.55, Some(2.00);
+    Tl = 81, 204.38, 1.45, 1.96, Some(1.62);
+    Pb = 82, 207.2, 1.46, 2.02, Some(2.33);
+    Bi = 83, 208.98, 1.48, 2.07, Some(2.02);
+    Po = 84, 209.0, 1.40, 1.97, Some(2.0);
+    At = 85, 210.0, 1.50, 2.02, Some(2.2);
+    Rn = 86, 222.0, 1.50, 2.20, Some(2.2);
+    Fr = 87, 223.0, 2.60, 3.48, Some(0.7);
+    Ra = 88, 226.0, 2.21, 2.83, Some(0.9);
+    Ac = 89, 227.0, 2.15, 2.00, Some(1.1);
+    Th = 90, 232.04, 2.06, 2.00, Some(1.3);
+    Pa = 91, 231.04, 2.00, 2.00, Some(1.5);
+    U = 92, 238.03, 1.96, 1.86, Some(1.38);
+    Np = 93, 237.0, 1.90, 2.00, Some(1.36);
+    Pu = 94, 244.0, 1.87, 2.00, Some(1.28);
+    Am = 95, 243.0, 1.80, 2.00, Some(1.3);
+    Cm = 96, 247.0, 1.69, 2.00, Some(1.3);
+    Bk = 97, 247.0, 1.68, 2.00, Some(1.3);
+    Cf = 98, 251.0, 1.68, 2.00, Some(1.3);
+    Es = 99, 252.0, 1.65, 2.00, Some(1.3);
+    Fm = 100, 257.0, 1.67, 2.00, Some(1.3);
+    Md = 101, 258.0, 1.73, 2.00, Some(1.3);
+    No = 102, 259.0, 1.76, 2.00, Some(1.3);
+    Lr = 103, 266.0, 1.61, 2.00, Some(1.3);
+    Rf = 104, 267.0, 1.57, 2.00, None;
+    Db = 105, 268.0, 1.49, 2.00, None;
+    Sg = 106, 269.0, 1.43, 2.00, None;
+    Bh = 107, 270.0, 1.41, 2.00, None;
+    Hs = 108, 270.0, 1.34, 2.00, None;
+    Mt = 109, 278.0, 1.29, 2.00, None;
+    Ds = 110, 281.0, 1.28, 2.00, None;
+    Rg = 111, 282.0, 1.21, 2.00, None;
+    Cn = 112, 285.0, 1.22, 2.00, None;
+    Nh = 113, 286.0, 1.36, 2.00, None;
+    Fl = 114, 289.0, 1.43, 2.00, None;
+    Mc = 115, 290.0, 1.62, 2.00, None;
+    Lv = 116, 293.0, 1.75, 2.00, None;
+    Ts = 117, 294.0, 1.65, 2.00, None;
+    Og = 118, 294.0, 1.57, 2.00, None;
+}
+
+/// Two letter elements which can be safely recognized at the start of an
+/// atom name, since no standard residue uses atom names starting with them
+const TWO_LETTER_PREFIXES: [Element; 8] = [
+    Element::Cl,
+    Element::Br,
+    Element::Fe,
+    Element::Zn,
+    Element::Mg,
+    Element::Mn,
+    Element::Cu,
+    Element::Se,
+];
+
+impl Element {
+    /// The atomic number
+    pub fn atomic_number(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Get the element for the given atomic number
+    pub fn from_atomic_number(number: u8) -> Option<Element> {
+        if number == 0 {
+            return None;
+        }
+        ELEMENTS.get(number as usize - 1).cloned()
+    }
+
+    /// Parse a case insensitive element symbol. Deuterium (`D`) and
+    /// tritium (`T`) are mapped to hydrogen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mmtf::element::Element;
+    ///
+    /// assert_eq!(Some(Element::Fe), Element::from_symbol("FE"));
+    /// assert_eq!(Some(Element::H), Element::from_symbol("D"));
+    /// assert_eq!(None, Element::from_symbol("Xx"));
+    /// ```
+    pub fn from_symbol(symbol: &str) -> Option<Element> {
+        let symbol = symbol.trim();
+        if symbol.eq_ignore_ascii_case("D") || symbol.eq_ignore_ascii_case("T") {
+            return Some(Element::H);
+        }
+        ELEMENTS
+            .iter()
+            .find(|e| e.symbol().eq_ignore_ascii_case(symbol))
+            .cloned()
+    }
+
+    /// Infer the element from an atom name and the name of its group,
+    /// following the `PDB` atom naming conventions.
+    ///
+    /// Single atom groups whose atom name equals the group name (ions such
+    /// as `CA`, `ZN` or `NA`) are read as an element symbol. Otherwise, leading
+    /// digits are skipped and the first letter is used, except for a few
+    /// unambiguous two letter prefixes like `CL`, `BR` or `FE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mmtf::element::Element;
+    ///
+    /// assert_eq!(Some(Element::C), Element::infer("CA", "ALA"));
+    /// assert_eq!(Some(Element::Ca), Element::infer("CA", "CA"));
+    /// assert_eq!(Some(Element::H), Element::infer("1HB", "ALA"));
+    /// assert_eq!(Some(Element::Fe), Element::infer("FE", "HEM"));
+    /// ```
+    pub fn infer(atom_name: &str, group_name: &str) -> Option<Element> {
+        let atom_name = atom_name.trim();
+        if atom_name.eq_ignore_ascii_case(group_name.trim()) {
+            if let Some(element) = Element::from_symbol(atom_name) {
+                return Some(element);
+            }
+        }
+
+        let name = atom_name.trim_start_matches(|c: char| c.is_ascii_digit());
+        if name.len() >= 2 {
+            if let Some(prefix) = name.get(..2) {
+                let prefixed = TWO_LETTER_PREFIXES
+                    .iter()
+                    .find(|e| e.symbol().eq_ignore_ascii_case(prefix));
+                if let Some(element) = prefixed {
+                    return Some(*element);
+                }
+            }
+        }
+        name.get(..1).and_then(Element::from_symbol)
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+impl GroupType {
+    /// Elements of the atoms in this group.
+    ///
+    /// Uses [`element_list`](#structfield.element_list) when present and
+    /// falls back to [`Element::infer`](../element/enum.Element.html#method.infer)
+    /// on the atom names for missing or unknown entries.
+    pub fn elements(&self) -> Vec<Option<Element>> {
+        self.atom_name_list
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                self.element_list
+                    .as_ref()
+                    .and_then(|list| list.get(index))
+                    .and_then(|symbol| Element::from_symbol(symbol))
+                    .or_else(|| Element::infer(name, &self.group_name))
+            })
+            .collect()
+    }
+}
+
+impl Mmtf {
+    /// Elements of all atoms in the structure, one entry for each atom.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    /// use mmtf::element::Element;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// let elements = mmtf.atom_elements();
+    ///
+    /// assert_eq!(mmtf.num_atoms as usize, elements.len());
+    /// assert_eq!(Some(Element::O), elements[0]);
+    /// ```
+    pub fn atom_elements(&self) -> Vec<Option<Element>> {
+        let group_elements: Vec<Vec<Option<Element>>> =
+            self.group_list.iter().map(GroupType::elements).collect();
+
+        let mut elements = Vec::with_capacity(self.num_atoms as usize);
+        for group_type in &self.group_type_list {
+            elements.extend_from_slice(&group_elements[*group_type as usize]);
+        }
+        elements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    fn group(names: &[&str], elements: Option<Vec<&str>>, group_name: &str) -> GroupType {
+        GroupType {
+            formal_charge_list: vec![0; names.len()],
+            atom_name_list: names.iter().map(|n| n.to_string()).collect(),
+            element_list: elements.map(|e| e.iter().map(|n| n.to_string()).collect()),
+            bond_atom_list: vec![],
+            bond_order_list: vec![],
+            group_name: group_name.to_string(),
+            single_letter_code: "?".to_string(),
+            chem_comp_type: "NON-POLYMER".to_string(),
+        }
+    }
+
+    #[test]
+    fn it_convert_atomic_numbers() {
+        assert_eq!(118, ELEMENTS.len());
+        for (index, element) in ELEMENTS.iter().enumerate() {
+            assert_eq!(index + 1, element.atomic_number() as usize);
+            assert_eq!(Some(*element), Element::from_atomic_number(element.atomic_number()));
+            assert_eq!(Some(*element), Element::from_symbol(element.symbol()));
+        }
+        assert_eq!(None, Element::from_atomic_number(0));
+        assert_eq!(None, Element::from_atomic_number(119));
+    }
+
+    #[test]
+    fn it_expose_element_properties() {
+        assert_eq!(26, Element::Fe.atomic_number());
+        assert_eq!(12.011, Element::C.mass());
+        assert_eq!(0.76, Element::C.covalent_radius());
+        assert_eq!(1.52, Element::O.vdw_radius());
+        assert_eq!(Some(3.98), Element::F.electronegativity());
+        assert_eq!(None, Element::Ar.electronegativity());
+        assert_eq!("Zn", Element::Zn.to_string());
+    }
+
+    #[test]
+    fn it_infer_element_from_atom_name() {
+        assert_eq!(Some(Element::N), Element::infer("N", "GLY"));
+        assert_eq!(Some(Element::O), Element::infer("OP1", "DG"));
+        assert_eq!(Some(Element::H), Element::infer("HG1", "THR"));
+        assert_eq!(Some(Element::H), Element::infer("2HD1", "LEU"));
+        assert_eq!(Some(Element::Se), Element::infer("SE", "MSE"));
+        assert_eq!(Some(Element::N), Element::infer("NA", "HEM"));
+        assert_eq!(Some(Element::Na), Element::infer("NA", "NA"));
+        assert_eq!(Some(Element::Cl), Element::infer("CL1", "LIG"));
+        assert_eq!(None, Element::infer("", "LIG"));
+    }
+
+    #[test]
+    fn it_fallback_to_inferred_elements() {
+        let ligand = group(&["C1", "CL1", "O1"], None, "LIG");
+        assert_eq!(
+            vec![Some(Element::C), Some(Element::Cl), Some(Element::O)],
+            ligand.elements()
+        );
+
+        let ligand = group(&["C1", "X1"], Some(vec!["C", "?"]), "LIG");
+        assert_eq!(vec![Some(Element::C), None], ligand.elements());
+    }
+
+    #[test]
+    fn it_list_atom_elements() {
+        let mmtf = load_173d();
+        let elements = mmtf.atom_elements();
+
+        assert_eq!(512, elements.len());
+        assert!(elements.iter().all(Option::is_some));
+
+        let inferred: Vec<Vec<Option<Element>>> = mmtf.group_list
+            .iter()
+            .map(|g| {
+                g.atom_name_list
+                    .iter()
+                    .map(|n| Element::infer(n, &g.group_name))
+                    .collect()
+            })
+            .collect();
+        let declared: Vec<Vec<Option<Element>>> =
+            mmtf.group_list.iter().map(GroupType::elements).collect();
+        assert_eq!(declared, inferred);
+    }
+}
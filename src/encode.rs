@@ -23,7 +23,7 @@ pub trait Strategy {
     fn apply(&mut self) -> Result<StrategyDataTypes, EncodeError>;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct HeaderLayout {
     pub codec: i32,
     pub length: i32,
@@ -0,0 +1,234 @@
+use std::io::{self, Write};
+
+use chain::UNKNOWN;
+use mmtf::{Entity, EntityType, Mmtf};
+
+/// Kind of sequences written by [`Mmtf::to_fasta`](../mmtf/struct.Mmtf.html#method.to_fasta)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FastaRecords {
+    /// One record per entity with the sequence of the full construct
+    #[default]
+    Entities,
+
+    /// One record per chain of the first model with the sequence of the observed groups
+    Chains,
+}
+
+/// Options of the FASTA export
+#[derive(Debug, Clone)]
+pub struct FastaOptions {
+    /// Kind of sequences to write
+    pub records: FastaRecords,
+
+    /// Only write polymer entities or chains of polymer entities
+    pub polymer_only: bool,
+
+    /// Number of residues per sequence line, `0` writes each sequence on a single line
+    pub line_width: usize,
+}
+
+impl Default for FastaOptions {
+    fn default() -> Self {
+        FastaOptions {
+            records: FastaRecords::Entities,
+            polymer_only: true,
+            line_width: 80,
+        }
+    }
+}
+
+fn write_record<W: Write>(
+    writer: &mut W,
+    header: &str,
+    sequence: &str,
+    line_width: usize,
+) -> io::Result<()> {
+    writeln!(writer, ">{}", header)?;
+    let codes: Vec<char> = sequence.chars().collect();
+    let width = if line_width == 0 {
+        codes.len()
+    } else {
+        line_width
+    };
+    for line in codes.chunks(width) {
+        writeln!(writer, "{}", line.iter().collect::<String>())?;
+    }
+    Ok(())
+}
+
+impl Mmtf {
+    /// Name of the given chain, falling back to its id
+    fn chain_label(&self, chain: usize) -> &str {
+        self.chain_name_list
+            .as_ref()
+            .map_or(&self.chain_id_list[chain], |names| &names[chain])
+    }
+
+    /// Record name prefixed with the structure id, if any
+    fn fasta_name(&self, name: &str) -> String {
+        match self.structure_id {
+            Some(ref id) => format!("{}_{}", id, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Write the entity or chain sequences in FASTA format.
+    ///
+    /// Entity records are named after the structure id and the 1-based entity
+    /// number, followed by the chain names and the entity description.
+    /// Chain records are named after the structure id and the chain id,
+    /// followed by the chain name and the description of its entity; groups
+    /// without single letter code are written as `X`. Records with an empty
+    /// sequence are skipped, as are chains of non-polymer entities, such as
+    /// waters, without any group of known single letter code.
+    pub fn write_fasta<W: Write>(&self, writer: &mut W, options: &FastaOptions) -> io::Result<()> {
+        let entities = self.entity_list.as_ref().map_or(&[][..], |e| &e[..]);
+        let is_polymer = |entity: &Entity| entity.entity_type() == EntityType::Polymer;
+
+        match options.records {
+            FastaRecords::Entities => {
+                for (index, entity) in entities.iter().enumerate() {
+                    if entity.sequence.is_empty() || (options.polymer_only && !is_polymer(entity)) {
+                        continue;
+                    }
+                    let mut names: Vec<&str> = Vec::new();
+                    for chain in &entity.chain_index_list {
+                        let name = self.chain_label(*chain as usize);
+                        if !names.contains(&name) {
+                            names.push(name);
+                        }
+                    }
+                    let header = format!(
+                        "{}|Chains {}|{}",
+                        self.fasta_name(&(index + 1).to_string()),
+                        names.join(", "),
+                        entity.description
+                    );
+                    write_record(writer, &header, &entity.sequence, options.line_width)?;
+                }
+            }
+            FastaRecords::Chains => {
+                let num_chains = self.chains_per_model.first().map_or(0, |n| *n as usize);
+                for chain in self.chains().into_iter().take(num_chains) {
+                    let entity = chain.entity();
+                    if options.polymer_only && !entity.is_some_and(is_polymer) {
+                        continue;
+                    }
+                    let sequence: String = chain
+                        .residue_codes()
+                        .iter()
+                        .map(|&(_, code)| code)
+                        .collect();
+                    let unknown = sequence.chars().all(|code| code == UNKNOWN);
+                    if sequence.is_empty() || (unknown && !entity.is_some_and(is_polymer)) {
+                        continue;
+                    }
+                    let header = format!(
+                        "{}|Chain {}|{}",
+                        self.fasta_name(chain.id()),
+                        self.chain_label(chain.index()),
+                        entity.map_or("", |entity| entity.description.as_str())
+                    );
+                    write_record(writer, &header, &sequence, options.line_width)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Entity or chain sequences in FASTA format, see [`write_fasta`](#method.write_fasta)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    /// use mmtf::fasta::FastaOptions;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// let fasta = mmtf.to_fasta(&FastaOptions::default());
+    ///
+    /// assert!(fasta.starts_with(">173D_1|Chains A, B|"));
+    /// assert_eq!(Some("GAAGCTTC"), fasta.lines().nth(1));
+    /// ```
+    pub fn to_fasta(&self, options: &FastaOptions) -> String {
+        let mut buffer = Vec::new();
+        self.write_fasta(&mut buffer, options)
+            .expect("writing to a vector can not fail");
+        String::from_utf8(buffer).expect("sequences are valid UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    #[test]
+    fn it_write_entities() {
+        let mmtf = load_173d();
+        let fasta = mmtf.to_fasta(&FastaOptions::default());
+
+        assert_eq!(
+            ">173D_1|Chains A, B|DNA (5'-D(*GP*AP*AP*GP*CP*TP*TP*C)-3')\n\
+             GAAGCTTC\n\
+             >173D_2|Chains C, D|ACTINOMYCIN D\n\
+             TXPGVXTXPGV\n",
+            fasta
+        );
+
+        // the water entity has no sequence
+        let options = FastaOptions {
+            polymer_only: false,
+            ..Default::default()
+        };
+        assert_eq!(fasta, mmtf.to_fasta(&options));
+    }
+
+    #[test]
+    fn it_write_observed_chains() {
+        let mmtf = load_173d();
+        let options = FastaOptions {
+            records: FastaRecords::Chains,
+            ..Default::default()
+        };
+        let fasta = mmtf.to_fasta(&options);
+        let lines: Vec<&str> = fasta.lines().collect();
+
+        assert_eq!(8, lines.len());
+        assert_eq!(">173D_C|Chain C|ACTINOMYCIN D", lines[4]);
+        assert_eq!("TVPGVX", lines[5]);
+        assert_eq!("XTVPGV", lines[7]);
+
+        let options = FastaOptions {
+            polymer_only: false,
+            ..options
+        };
+        // water chains have no residue codes and are still skipped
+        let fasta = mmtf.to_fasta(&options);
+        assert_eq!(8, fasta.lines().count());
+        assert!(!fasta.contains("|water"));
+    }
+
+    #[test]
+    fn it_wrap_sequence_lines() {
+        let mut mmtf = load_173d();
+        mmtf.structure_id = None;
+        let options = FastaOptions {
+            line_width: 4,
+            ..Default::default()
+        };
+        let fasta = mmtf.to_fasta(&options);
+        let lines: Vec<&str> = fasta.lines().collect();
+
+        assert!(lines[0].starts_with(">1|"));
+        assert_eq!(vec!["GAAG", "CTTC"], lines[1..3].to_vec());
+        assert_eq!(vec!["TXPG", "VXTX", "PGV"], lines[4..7].to_vec());
+    }
+}
@@ -0,0 +1,428 @@
+use mmtf::Mmtf;
+use selection::AtomSet;
+use superposition::{symmetric_eigen, to_f64};
+
+/// Axis-aligned box enclosing a set of atoms
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    /// Smallest coordinates
+    pub min: [f64; 3],
+
+    /// Largest coordinates
+    pub max: [f64; 3],
+}
+
+impl BoundingBox {
+    /// Center of the box
+    pub fn center(&self) -> [f64; 3] {
+        [
+            (self.min[0] + self.max[0]) / 2.0,
+            (self.min[1] + self.max[1]) / 2.0,
+            (self.min[2] + self.max[2]) / 2.0,
+        ]
+    }
+
+    /// Edge lengths of the box
+    pub fn size(&self) -> [f64; 3] {
+        [
+            self.max[0] - self.min[0],
+            self.max[1] - self.min[1],
+            self.max[2] - self.min[2],
+        ]
+    }
+}
+
+/// Box enclosing a set of atoms, aligned to the principal components of
+/// their positions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrientedBoundingBox {
+    /// Center of the box
+    pub center: [f64; 3],
+
+    /// Unit vectors along the box edges, the direction of the largest spread
+    /// first, forming a right-handed frame
+    pub axes: [[f64; 3]; 3],
+
+    /// Half the edge length along each axis
+    pub half_extents: [f64; 3],
+}
+
+impl OrientedBoundingBox {
+    /// Volume of the box in **Å³**
+    pub fn volume(&self) -> f64 {
+        8.0 * self.half_extents[0] * self.half_extents[1] * self.half_extents[2]
+    }
+
+    /// The eight corners of the box
+    pub fn corners(&self) -> [[f64; 3]; 8] {
+        let mut corners = [self.center; 8];
+        for (index, corner) in corners.iter_mut().enumerate() {
+            for k in 0..3 {
+                let sign = if index & (1 << k) != 0 { 1.0 } else { -1.0 };
+                for (c, a) in corner.iter_mut().zip(&self.axes[k]) {
+                    *c += sign * self.half_extents[k] * a;
+                }
+            }
+        }
+        corners
+    }
+}
+
+/// Principal moments and axes of inertia
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrincipalAxes {
+    /// Center of mass the moments refer to
+    pub center: [f64; 3],
+
+    /// Principal moments of inertia in **Da Å²**, in ascending order
+    pub moments: [f64; 3],
+
+    /// Unit vector of the principal axis of each moment
+    pub axes: [[f64; 3]; 3],
+}
+
+/// Eigenvectors of a symmetric 3x3 matrix as rows, ordered by ascending
+/// eigenvalue, and forming a right-handed frame
+fn sorted_eigen(matrix: [[f64; 3]; 3]) -> ([f64; 3], [[f64; 3]; 3]) {
+    let (values, vectors) = symmetric_eigen(matrix);
+    let mut order = [0, 1, 2];
+    order.sort_by(|i, j| values[*i].total_cmp(&values[*j]));
+
+    let column = |i: usize| [vectors[0][i], vectors[1][i], vectors[2][i]];
+    let (a, b) = (column(order[0]), column(order[1]));
+    let c = [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ];
+    (
+        [values[order[0]], values[order[1]], values[order[2]]],
+        [a, b, c],
+    )
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+impl Mmtf {
+    /// Coordinates and masses of the selected atoms, masses of unknown elements are `1.0`
+    fn weighted_points(&self, atoms: &AtomSet) -> (Vec<[f64; 3]>, Vec<f64>) {
+        let elements = self.atom_elements();
+        atoms
+            .iter()
+            .map(|atom| {
+                (
+                    to_f64(self.position(atom)),
+                    elements[atom].map_or(1.0, |element| f64::from(element.mass())),
+                )
+            })
+            .unzip()
+    }
+
+    /// Mean position of the selected atoms, `None` if no atom is selected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    /// use mmtf::selection::AtomSet;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// let all = AtomSet::all(mmtf.num_atoms as usize);
+    ///
+    /// let center = mmtf.centroid(&all).unwrap();
+    /// let bounds = mmtf.bounding_box(&all).unwrap();
+    /// assert!((0..3).all(|k| bounds.min[k] <= center[k] && center[k] <= bounds.max[k]));
+    /// assert!(mmtf.radius_of_gyration(&all).unwrap() > 0.0);
+    /// ```
+    pub fn centroid(&self, atoms: &AtomSet) -> Option<[f64; 3]> {
+        let points: Vec<[f64; 3]> = atoms
+            .iter()
+            .map(|atom| to_f64(self.position(atom)))
+            .collect();
+        mean(&points, &vec![1.0; points.len()])
+    }
+
+    /// Mass-weighted mean position of the selected atoms, `None` if no atom is selected
+    pub fn center_of_mass(&self, atoms: &AtomSet) -> Option<[f64; 3]> {
+        let (points, masses) = self.weighted_points(atoms);
+        mean(&points, &masses)
+    }
+
+    /// Mass-weighted radius of gyration of the selected atoms in **Å**,
+    /// `None` if no atom is selected
+    pub fn radius_of_gyration(&self, atoms: &AtomSet) -> Option<f64> {
+        let (points, masses) = self.weighted_points(atoms);
+        let center = mean(&points, &masses)?;
+        let total: f64 = masses.iter().sum();
+        let sum: f64 = points
+            .iter()
+            .zip(&masses)
+            .map(|(point, mass)| {
+                let d = [
+                    point[0] - center[0],
+                    point[1] - center[1],
+                    point[2] - center[2],
+                ];
+                mass * dot(d, d)
+            })
+            .sum();
+        Some((sum / total).sqrt())
+    }
+
+    /// Axis-aligned bounding box of the selected atoms, `None` if no atom is selected
+    pub fn bounding_box(&self, atoms: &AtomSet) -> Option<BoundingBox> {
+        let mut points = atoms.iter().map(|atom| to_f64(self.position(atom)));
+        let first = points.next()?;
+        Some(points.fold(
+            BoundingBox {
+                min: first,
+                max: first,
+            },
+            |mut bounds, point| {
+                for (k, value) in point.iter().enumerate() {
+                    bounds.min[k] = bounds.min[k].min(*value);
+                    bounds.max[k] = bounds.max[k].max(*value);
+                }
+                bounds
+            },
+        ))
+    }
+
+    /// Bounding box of the selected atoms aligned to the principal components
+    /// of their positions, `None` if no atom is selected
+    pub fn oriented_bounding_box(&self, atoms: &AtomSet) -> Option<OrientedBoundingBox> {
+        let points: Vec<[f64; 3]> = atoms
+            .iter()
+            .map(|atom| to_f64(self.position(atom)))
+            .collect();
+        let centroid = mean(&points, &vec![1.0; points.len()])?;
+
+        let mut covariance = [[0.0; 3]; 3];
+        for point in &points {
+            for i in 0..3 {
+                for j in 0..3 {
+                    covariance[i][j] += (point[i] - centroid[i]) * (point[j] - centroid[j]);
+                }
+            }
+        }
+        let (_, ascending) = sorted_eigen(covariance);
+        // reversing the order flips the handedness, negating one axis restores it
+        let smallest = ascending[0];
+        let axes = [
+            ascending[2],
+            ascending[1],
+            [-smallest[0], -smallest[1], -smallest[2]],
+        ];
+
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for point in &points {
+            for (k, axis) in axes.iter().enumerate() {
+                let projection = dot(*point, *axis);
+                min[k] = min[k].min(projection);
+                max[k] = max[k].max(projection);
+            }
+        }
+        let mut center = [0.0; 3];
+        let mut half_extents = [0.0; 3];
+        for (k, axis) in axes.iter().enumerate() {
+            half_extents[k] = (max[k] - min[k]) / 2.0;
+            let middle = (max[k] + min[k]) / 2.0;
+            for (c, a) in center.iter_mut().zip(axis) {
+                *c += middle * a;
+            }
+        }
+        Some(OrientedBoundingBox {
+            center,
+            axes,
+            half_extents,
+        })
+    }
+
+    /// Inertia tensor of the selected atoms about their center of mass in
+    /// **Da Å²**, `None` if no atom is selected
+    pub fn inertia_tensor(&self, atoms: &AtomSet) -> Option<[[f64; 3]; 3]> {
+        let (points, masses) = self.weighted_points(atoms);
+        let center = mean(&points, &masses)?;
+        let mut tensor = [[0.0; 3]; 3];
+        for (point, mass) in points.iter().zip(&masses) {
+            let d = [
+                point[0] - center[0],
+                point[1] - center[1],
+                point[2] - center[2],
+            ];
+            let squared = dot(d, d);
+            for i in 0..3 {
+                for j in 0..3 {
+                    let identity = if i == j { squared } else { 0.0 };
+                    tensor[i][j] += mass * (identity - d[i] * d[j]);
+                }
+            }
+        }
+        Some(tensor)
+    }
+
+    /// Principal moments and axes of inertia of the selected atoms, `None`
+    /// if no atom is selected
+    pub fn principal_axes(&self, atoms: &AtomSet) -> Option<PrincipalAxes> {
+        let tensor = self.inertia_tensor(atoms)?;
+        let center = self.center_of_mass(atoms)?;
+        let (moments, axes) = sorted_eigen(tensor);
+        Some(PrincipalAxes {
+            center,
+            moments,
+            axes,
+        })
+    }
+}
+
+/// Weighted mean of the points, `None` if there are none
+fn mean(points: &[[f64; 3]], weights: &[f64]) -> Option<[f64; 3]> {
+    let total: f64 = weights.iter().sum();
+    if points.is_empty() || total == 0.0 {
+        return None;
+    }
+    let mut center = [0.0; 3];
+    for (point, weight) in points.iter().zip(weights) {
+        for k in 0..3 {
+            center[k] += point[k] * weight / total;
+        }
+    }
+    Some(center)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    /// Structure with the coordinates of the first atoms replaced by `points`
+    fn with_points(points: &[[f32; 3]]) -> (Mmtf, AtomSet) {
+        let mut mmtf = load_173d();
+        for (atom, point) in points.iter().enumerate() {
+            mmtf.x_coord_list[atom] = point[0];
+            mmtf.y_coord_list[atom] = point[1];
+            mmtf.z_coord_list[atom] = point[2];
+        }
+        let atoms = AtomSet::from_indices(512, 0..points.len());
+        (mmtf, atoms)
+    }
+
+    #[test]
+    fn it_decompose_symmetric_matrix() {
+        let (values, vectors) =
+            symmetric_eigen([[2.0, 1.0, 0.0], [1.0, 2.0, 0.0], [0.0, 0.0, 5.0]]);
+        let mut sorted = values;
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for (value, expected) in sorted.iter().zip(&[1.0, 3.0, 5.0]) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+        let largest = (0..3).find(|i| (values[*i] - 5.0).abs() < 1e-9).unwrap();
+        assert!((vectors[2][largest].abs() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_handle_non_finite_coordinates() {
+        let (mut mmtf, atoms) = with_points(&[[0.0, 0.0, 0.0], [1.0, 2.0, 0.0], [3.0, 0.0, 1.0]]);
+        mmtf.y_coord_list[1] = f32::NAN;
+        assert!(mmtf.oriented_bounding_box(&atoms).is_some());
+        assert!(mmtf.principal_axes(&atoms).is_some());
+    }
+
+    #[test]
+    fn it_compute_centers_and_extent() {
+        let (mmtf, atoms) = with_points(&[
+            [0.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [2.0, 4.0, 0.0],
+            [0.0, 4.0, 0.0],
+        ]);
+        assert_eq!(Some([1.0, 2.0, 0.0]), mmtf.centroid(&atoms));
+
+        let bounds = mmtf.bounding_box(&atoms).unwrap();
+        assert_eq!([0.0, 0.0, 0.0], bounds.min);
+        assert_eq!([2.0, 4.0, 0.0], bounds.size());
+        assert_eq!([1.0, 2.0, 0.0], bounds.center());
+
+        // the first atoms of 173D are O, C, C and O, so the oxygens pull
+        // the center of mass towards x = 0
+        let center = mmtf.center_of_mass(&atoms).unwrap();
+        assert!(center[0] < 1.0 && (center[1] - 2.0).abs() < 1e-9);
+
+        let empty = AtomSet::empty(512);
+        assert_eq!(None, mmtf.centroid(&empty));
+        assert_eq!(None, mmtf.bounding_box(&empty));
+        assert_eq!(None, mmtf.principal_axes(&empty));
+    }
+
+    #[test]
+    fn it_compute_radius_of_gyration() {
+        let (mmtf, atoms) = with_points(&[[0.0, 0.0, 0.0], [0.0, 0.0, 0.0]]);
+        assert_eq!(Some(0.0), mmtf.radius_of_gyration(&atoms));
+
+        // oxygen atoms of equal mass at distance 2
+        let (mmtf, _) =
+            with_points(&[[-1.0, 0.0, 0.0], [0.0; 3], [0.0, 0.0, 0.0], [1.0, 0.0, 0.0]]);
+        let oxygens = AtomSet::from_indices(512, vec![0, 3]);
+        assert!((mmtf.radius_of_gyration(&oxygens).unwrap() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_compute_principal_axes() {
+        let (mmtf, _) = with_points(&[
+            [-3.0, 0.0, 0.0],
+            [0.0; 3],
+            [0.0, -1.0, 0.0],
+            [3.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ]);
+        let atoms = AtomSet::from_indices(512, vec![0, 2, 3, 4]);
+        let axes = mmtf.principal_axes(&atoms).unwrap();
+
+        // the smallest moment belongs to the long axis along x
+        assert!(axes.moments[0] < axes.moments[1] && axes.moments[1] < axes.moments[2]);
+        assert!((axes.axes[0][0].abs() - 1.0).abs() < 1e-9);
+        assert!((axes.axes[2][2].abs() - 1.0).abs() < 1e-9);
+        let tensor = mmtf.inertia_tensor(&atoms).unwrap();
+        assert!(
+            (tensor[0][0] + tensor[1][1] + tensor[2][2] - axes.moments.iter().sum::<f64>()).abs()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn it_compute_oriented_bounding_box() {
+        let mmtf = load_173d();
+        let all = AtomSet::all(512);
+        let oriented = mmtf.oriented_bounding_box(&all).unwrap();
+
+        assert!(oriented.half_extents[0] >= oriented.half_extents[2]);
+        // the axes form a right-handed frame
+        let [a, b, c] = oriented.axes;
+        let determinant = a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+            + a[2] * (b[0] * c[1] - b[1] * c[0]);
+        assert!((determinant - 1.0).abs() < 1e-6);
+        // every atom lies within the box
+        for atom in 0..512 {
+            let point = to_f64(mmtf.position(atom));
+            for (axis, half_extent) in oriented.axes.iter().zip(&oriented.half_extents) {
+                let offset = (0..3)
+                    .map(|k| (point[k] - oriented.center[k]) * axis[k])
+                    .sum::<f64>();
+                assert!(offset.abs() <= half_extent + 1e-6);
+            }
+        }
+        let corners = oriented.corners();
+        let mean: f64 = corners.iter().map(|corner| corner[0]).sum::<f64>() / 8.0;
+        assert!((mean - oriented.center[0]).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,370 @@
+use element::Element;
+use mmtf::{GroupType, Mmtf};
+use neighbors::NeighborSearch;
+
+/// Hydrogen bond between a donor and an acceptor atom
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HydrogenBond {
+    /// Heavy atom the hydrogen is bonded to
+    pub donor: usize,
+
+    /// Explicit hydrogen atom, `None` if the geometry was inferred
+    pub hydrogen: Option<usize>,
+
+    /// Atom accepting the hydrogen
+    pub acceptor: usize,
+
+    /// Donor–acceptor distance in **Å**
+    pub distance: f32,
+
+    /// Donor–hydrogen–acceptor angle in degrees, `None` if the geometry was inferred
+    pub angle: Option<f32>,
+}
+
+/// Geometric hydrogen bond detection
+///
+/// Donors and acceptors are typed by their element and, for standard amino
+/// acids, nucleotides and water, by their residue and atom name. Other
+/// nitrogen and oxygen atoms are treated as both donor and acceptor.
+///
+/// Groups with explicit hydrogens donate only through the polar atoms
+/// bonded to one, and bonds must satisfy the hydrogen–acceptor distance and
+/// the donor–hydrogen–acceptor angle. Without hydrogens the geometry is
+/// inferred from the angles the donor–acceptor axis makes with the heavy
+/// atoms bonded to the donor and the acceptor.
+#[derive(Debug, Clone)]
+pub struct HydrogenBondCriteria {
+    /// Largest donor–acceptor distance in **Å**
+    pub max_distance: f32,
+
+    /// Largest hydrogen–acceptor distance in **Å** with explicit hydrogens
+    pub max_hydrogen_distance: f32,
+
+    /// Smallest donor–hydrogen–acceptor angle in degrees with explicit hydrogens
+    pub min_angle: f32,
+
+    /// Smallest angle in degrees between the donor–acceptor axis and the
+    /// bonds of the donor and of the acceptor to their heavy atom neighbors
+    pub min_antecedent_angle: f32,
+
+    /// Whether bonds between atoms of the same group are reported
+    pub include_intra_group: bool,
+}
+
+impl Default for HydrogenBondCriteria {
+    fn default() -> Self {
+        HydrogenBondCriteria {
+            max_distance: 3.5,
+            max_hydrogen_distance: 2.5,
+            min_angle: 120.0,
+            min_antecedent_angle: 90.0,
+            include_intra_group: false,
+        }
+    }
+}
+
+/// Whether the named atom of a standard group can donate (`.0`) and accept
+/// (`.1`) a hydrogen bond, `None` if the atom is not tabulated
+fn residue_role(group_type: &GroupType, atom_name: &str) -> Option<(bool, bool)> {
+    let group_name = group_type.group_name.to_ascii_uppercase();
+    if group_type.is_water() {
+        return Some((true, true));
+    }
+    if group_type.is_amino_acid() {
+        let role = match (group_name.as_str(), atom_name) {
+            ("PRO", "N") => (false, false),
+            (_, "N") => (true, false),
+            (_, "O") => (false, true),
+            (_, "OXT") => (true, true),
+            ("ARG", "NE") | ("ARG", "NH1") | ("ARG", "NH2") => (true, false),
+            ("ASN", "ND2") | ("GLN", "NE2") | ("LYS", "NZ") | ("TRP", "NE1") => (true, false),
+            ("ASN", "OD1") | ("GLN", "OE1") => (false, true),
+            ("ASP", "OD1") | ("ASP", "OD2") | ("GLU", "OE1") | ("GLU", "OE2") => (false, true),
+            ("HIS", "ND1") | ("HIS", "NE2") => (true, true),
+            ("SER", "OG") | ("THR", "OG1") | ("TYR", "OH") | ("CYS", "SG") => (true, true),
+            ("MET", "SD") => (false, true),
+            _ => return None,
+        };
+        return Some(role);
+    }
+    if group_type.is_nucleotide() {
+        let base = group_name.trim_start_matches('D');
+        let role = match (base, atom_name) {
+            (_, "O2'") => (true, true),
+            (_, name) if name.starts_with('O') => (false, true),
+            ("G", "N1") | ("G", "N2") | ("A", "N6") | ("C", "N4") | ("U", "N3") | ("T", "N3") => {
+                (true, false)
+            }
+            ("A", "N1") | ("A", "N3") | ("A", "N7") | ("G", "N3") | ("G", "N7") | ("C", "N3") => {
+                (false, true)
+            }
+            _ => (false, false),
+        };
+        return Some(role);
+    }
+    None
+}
+
+/// Angle at `b` in degrees
+fn angle(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> f32 {
+    let u = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+    let v = [c[0] - b[0], c[1] - b[1], c[2] - b[2]];
+    let dot = u[0] * v[0] + u[1] * v[1] + u[2] * v[2];
+    let norm = (u[0] * u[0] + u[1] * u[1] + u[2] * u[2]).sqrt()
+        * (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if norm == 0.0 {
+        return 0.0;
+    }
+    (dot / norm).clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+impl HydrogenBondCriteria {
+    /// Find all hydrogen bonds within each model of the structure, ordered
+    /// by donor, hydrogen and acceptor
+    pub fn find(&self, mmtf: &Mmtf) -> Vec<HydrogenBond> {
+        let hierarchy = mmtf.hierarchy();
+        let elements = mmtf.atom_elements();
+        let graph = mmtf.bond_graph();
+        let is_hydrogen = |atom: usize| elements[atom] == Some(Element::H);
+
+        let group_has_hydrogens: Vec<bool> = hierarchy
+            .group_atoms
+            .iter()
+            .map(|atoms| atoms.clone().any(is_hydrogen))
+            .collect();
+        let mut donors = vec![false; hierarchy.num_atoms()];
+        let mut acceptors = vec![false; hierarchy.num_atoms()];
+        for (group, atoms) in hierarchy.group_atoms.iter().enumerate() {
+            let group_type = &mmtf.group_list[mmtf.group_type_list[group] as usize];
+            for (index, atom) in atoms.clone().enumerate() {
+                let (donor, acceptor) =
+                    match residue_role(group_type, &group_type.atom_name_list[index]) {
+                        Some(role) => role,
+                        None => match elements[atom] {
+                            Some(Element::N) | Some(Element::O) => (true, true),
+                            _ => (false, false),
+                        },
+                    };
+                donors[atom] = if group_has_hydrogens[group] {
+                    (donor || acceptor) && graph.neighbors(atom).any(is_hydrogen)
+                } else {
+                    donor
+                };
+                acceptors[atom] = acceptor;
+            }
+        }
+
+        let search = NeighborSearch::with_atoms(
+            mmtf,
+            (0..hierarchy.num_atoms()).filter(|atom| acceptors[*atom]),
+            self.max_distance,
+        );
+        let heavy_neighbors = |atom: usize| graph.neighbors(atom).filter(move |n| !is_hydrogen(*n));
+
+        let mut bonds = Vec::new();
+        for donor in (0..hierarchy.num_atoms()).filter(|atom| donors[*atom]) {
+            let donor_position = mmtf.position(donor);
+            let hydrogens: Vec<usize> =
+                graph.neighbors(donor).filter(|n| is_hydrogen(*n)).collect();
+            for (acceptor, d) in search.within(donor_position, self.max_distance) {
+                if acceptor == donor
+                    || hierarchy.atom_model(acceptor) != hierarchy.atom_model(donor)
+                    || (!self.include_intra_group
+                        && hierarchy.atom_group[acceptor] == hierarchy.atom_group[donor])
+                    || graph.bond(donor, acceptor).is_some()
+                {
+                    continue;
+                }
+                let acceptor_position = mmtf.position(acceptor);
+                let antecedents =
+                    heavy_neighbors(donor).filter(|n| *n != acceptor).all(|n| {
+                        angle(mmtf.position(n), donor_position, acceptor_position)
+                            >= self.min_antecedent_angle
+                    }) && heavy_neighbors(acceptor).filter(|n| *n != donor).all(|n| {
+                        angle(donor_position, acceptor_position, mmtf.position(n))
+                            >= self.min_antecedent_angle
+                    });
+                if !antecedents {
+                    continue;
+                }
+
+                if hydrogens.is_empty() {
+                    bonds.push(HydrogenBond {
+                        donor,
+                        hydrogen: None,
+                        acceptor,
+                        distance: d,
+                        angle: None,
+                    });
+                }
+                for &hydrogen in &hydrogens {
+                    let hydrogen_position = mmtf.position(hydrogen);
+                    let angle = angle(donor_position, hydrogen_position, acceptor_position);
+                    if distance(hydrogen_position, acceptor_position) <= self.max_hydrogen_distance
+                        && angle >= self.min_angle
+                    {
+                        bonds.push(HydrogenBond {
+                            donor,
+                            hydrogen: Some(hydrogen),
+                            acceptor,
+                            distance: d,
+                            angle: Some(angle),
+                        });
+                    }
+                }
+            }
+        }
+        bonds.sort_by_key(|bond| (bond.donor, bond.hydrogen, bond.acceptor));
+        bonds
+    }
+}
+
+impl Mmtf {
+    /// Hydrogen bonds with the default [`HydrogenBondCriteria`](../hbonds/struct.HydrogenBondCriteria.html)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// let bonds = mmtf.hydrogen_bonds();
+    ///
+    /// assert!(bonds.iter().all(|bond| bond.distance <= 3.5));
+    /// ```
+    pub fn hydrogen_bonds(&self) -> Vec<HydrogenBond> {
+        HydrogenBondCriteria::default().find(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    fn atom_name(mmtf: &Mmtf, atom: usize) -> (String, String) {
+        let hierarchy = mmtf.hierarchy();
+        let group = hierarchy.atom_group[atom];
+        let group_type = &mmtf.group_list[mmtf.group_type_list[group] as usize];
+        (
+            group_type.group_name.clone(),
+            group_type.atom_name_list[atom - hierarchy.group_atoms[group].start].clone(),
+        )
+    }
+
+    #[test]
+    fn it_type_donors_and_acceptors() {
+        let mmtf = load_173d();
+        let group_type = |name: &str| {
+            mmtf.group_list
+                .iter()
+                .find(|group_type| group_type.group_name == name)
+                .unwrap()
+        };
+        assert_eq!(Some((true, false)), residue_role(group_type("THR"), "N"));
+        assert_eq!(Some((false, false)), residue_role(group_type("PRO"), "N"));
+        assert_eq!(Some((true, true)), residue_role(group_type("THR"), "OG1"));
+        assert_eq!(Some((true, false)), residue_role(group_type("DG"), "N1"));
+        assert_eq!(Some((false, true)), residue_role(group_type("DG"), "O6"));
+        assert_eq!(Some((false, false)), residue_role(group_type("DG"), "N9"));
+        assert_eq!(Some((true, true)), residue_role(group_type("HOH"), "O"));
+        assert_eq!(None, residue_role(group_type("PXZ"), "O"));
+    }
+
+    #[test]
+    fn it_find_inferred_bonds() {
+        let mmtf = load_173d();
+        let bonds = mmtf.hydrogen_bonds();
+        let hierarchy = mmtf.hierarchy();
+
+        assert!(!bonds.is_empty());
+        for bond in &bonds {
+            assert!(bond.distance <= 3.5);
+            assert_eq!(None, bond.hydrogen);
+            assert_ne!(
+                hierarchy.atom_group[bond.donor],
+                hierarchy.atom_group[bond.acceptor]
+            );
+        }
+        // actinomycin D binds the guanine minor groove with its threonines
+        assert!(bonds.iter().any(|bond| {
+            atom_name(&mmtf, bond.donor) == ("THR".to_string(), "N".to_string())
+                && atom_name(&mmtf, bond.acceptor) == ("DG".to_string(), "N3".to_string())
+        }));
+        // proline has no backbone donor
+        assert!(!bonds
+            .iter()
+            .any(|bond| atom_name(&mmtf, bond.donor) == ("PRO".to_string(), "N".to_string())));
+    }
+
+    #[test]
+    fn it_use_explicit_hydrogens() {
+        let mmtf = load_173d();
+        let bond = mmtf.hydrogen_bonds()[0];
+        let hierarchy = mmtf.hierarchy();
+
+        // place a hydrogen on the line from the donor to the acceptor
+        let mut with_hydrogen = mmtf.clone();
+        let donor = with_hydrogen.position(bond.donor);
+        let acceptor = with_hydrogen.position(bond.acceptor);
+        let hydrogen: Vec<f32> = (0..3)
+            .map(|k| donor[k] + (acceptor[k] - donor[k]) / bond.distance)
+            .collect();
+        let group = hierarchy.atom_group[bond.donor];
+        let mut group_type =
+            with_hydrogen.group_list[with_hydrogen.group_type_list[group] as usize].clone();
+        let local = bond.donor - hierarchy.group_atoms[group].start;
+        let end = hierarchy.group_atoms[group].end;
+        group_type.atom_name_list.push("HX".to_string());
+        group_type.formal_charge_list.push(0);
+        if let Some(ref mut elements) = group_type.element_list {
+            elements.push("H".to_string());
+        }
+        group_type
+            .bond_atom_list
+            .extend(&[local as i32, group_type.atom_name_list.len() as i32 - 1]);
+        group_type.bond_order_list.push(1);
+        with_hydrogen.group_list.push(group_type);
+        with_hydrogen.group_type_list[group] = with_hydrogen.group_list.len() as i32 - 1;
+        with_hydrogen.x_coord_list.insert(end, hydrogen[0]);
+        with_hydrogen.y_coord_list.insert(end, hydrogen[1]);
+        with_hydrogen.z_coord_list.insert(end, hydrogen[2]);
+        for index in with_hydrogen.bond_atom_list.iter_mut() {
+            if *index as usize >= end {
+                *index += 1;
+            }
+        }
+        let shift = |atom: usize| if atom >= end { atom + 1 } else { atom };
+
+        let bonds = with_hydrogen.hydrogen_bonds();
+        let explicit = bonds
+            .iter()
+            .find(|other| {
+                other.donor == shift(bond.donor) && other.acceptor == shift(bond.acceptor)
+            })
+            .unwrap();
+        assert_eq!(Some(end), explicit.hydrogen);
+        assert!(explicit.angle.is_some_and(|angle| angle > 179.0));
+
+        // with a hydrogen present, the donor angle decides
+        let strict = HydrogenBondCriteria {
+            min_angle: 181.0,
+            ..Default::default()
+        };
+        assert!(!strict
+            .find(&with_hydrogen)
+            .iter()
+            .any(|other| other.donor == shift(bond.donor)));
+    }
+}
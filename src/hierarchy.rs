@@ -0,0 +1,196 @@
+use std::ops::Range;
+
+use mmtf::Mmtf;
+
+/// Index ranges of the structure hierarchy
+///
+/// MMTF stores models, chains, groups and atoms as flat lists which are
+/// implicitly linked by [`Mmtf.chains_per_model`](../mmtf/struct.Mmtf.html#structfield.chains_per_model),
+/// [`Mmtf.groups_per_chain`](../mmtf/struct.Mmtf.html#structfield.groups_per_chain) and the
+/// number of atoms of each group type. `Hierarchy` resolves these links once,
+/// so that the members and the parent of any entry can be looked up directly.
+///
+/// # Examples
+///
+/// ```
+/// # use std::path::Path;
+/// # use std::env;
+/// use std::fs::File;
+/// use mmtf::Mmtf;
+///
+/// # let file_path = Path::new(&env::current_dir().unwrap())
+/// #                                    .join("tests")
+/// #                                    .join("data")
+/// #                                    .join("173D.mmtf");
+/// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+/// let hierarchy = mmtf.hierarchy();
+///
+/// assert_eq!(0..8, hierarchy.model_chains[0]);
+/// assert_eq!(0..8, hierarchy.chain_groups[0]);
+/// assert_eq!(0, hierarchy.atom_chain(0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Hierarchy {
+    /// Range of chain indices of each model
+    pub model_chains: Vec<Range<usize>>,
+
+    /// Range of group indices of each chain
+    pub chain_groups: Vec<Range<usize>>,
+
+    /// Range of atom indices of each group
+    pub group_atoms: Vec<Range<usize>>,
+
+    /// Index of the model each chain belongs to
+    pub chain_model: Vec<usize>,
+
+    /// Index of the chain each group belongs to
+    pub group_chain: Vec<usize>,
+
+    /// Index of the group each atom belongs to
+    pub atom_group: Vec<usize>,
+}
+
+/// Split `0..total` into consecutive ranges of the given sizes
+fn ranges<I: IntoIterator<Item = usize>>(sizes: I) -> Vec<Range<usize>> {
+    let mut start = 0;
+    sizes
+        .into_iter()
+        .map(|size| {
+            let range = start..start + size;
+            start += size;
+            range
+        })
+        .collect()
+}
+
+/// Invert a list of ranges into the index of the range each entry belongs to
+fn parents(ranges: &[Range<usize>]) -> Vec<usize> {
+    let mut parents = Vec::with_capacity(ranges.last().map_or(0, |r| r.end));
+    for (index, range) in ranges.iter().enumerate() {
+        parents.extend(range.clone().map(|_| index));
+    }
+    parents
+}
+
+impl Hierarchy {
+    /// Build the hierarchy of the given structure
+    pub fn new(mmtf: &Mmtf) -> Self {
+        let model_chains = ranges(mmtf.chains_per_model.iter().map(|n| *n as usize));
+        let chain_groups = ranges(mmtf.groups_per_chain.iter().map(|n| *n as usize));
+        let group_atoms = ranges(mmtf.group_type_list.iter().map(|group_type| {
+            mmtf.group_list[*group_type as usize].atom_name_list.len()
+        }));
+
+        Hierarchy {
+            chain_model: parents(&model_chains),
+            group_chain: parents(&chain_groups),
+            atom_group: parents(&group_atoms),
+            model_chains,
+            chain_groups,
+            group_atoms,
+        }
+    }
+
+    /// Number of models
+    pub fn num_models(&self) -> usize {
+        self.model_chains.len()
+    }
+
+    /// Number of chains
+    pub fn num_chains(&self) -> usize {
+        self.chain_groups.len()
+    }
+
+    /// Number of groups
+    pub fn num_groups(&self) -> usize {
+        self.group_atoms.len()
+    }
+
+    /// Number of atoms
+    pub fn num_atoms(&self) -> usize {
+        self.atom_group.len()
+    }
+
+    /// Range of atom indices of the given chain
+    pub fn chain_atoms(&self, chain: usize) -> Range<usize> {
+        let groups = &self.chain_groups[chain];
+        self.group_range_atoms(groups)
+    }
+
+    /// Range of group indices of the given model
+    pub fn model_groups(&self, model: usize) -> Range<usize> {
+        let chains = &self.model_chains[model];
+        if chains.start == chains.end {
+            let start = self.chain_groups
+                .get(chains.start)
+                .map_or(self.num_groups(), |g| g.start);
+            return start..start;
+        }
+        self.chain_groups[chains.start].start..self.chain_groups[chains.end - 1].end
+    }
+
+    /// Range of atom indices of the given model
+    pub fn model_atoms(&self, model: usize) -> Range<usize> {
+        let groups = self.model_groups(model);
+        self.group_range_atoms(&groups)
+    }
+
+    /// Index of the chain the given atom belongs to
+    pub fn atom_chain(&self, atom: usize) -> usize {
+        self.group_chain[self.atom_group[atom]]
+    }
+
+    /// Index of the model the given atom belongs to
+    pub fn atom_model(&self, atom: usize) -> usize {
+        self.chain_model[self.atom_chain(atom)]
+    }
+
+    fn group_range_atoms(&self, groups: &Range<usize>) -> Range<usize> {
+        if groups.start == groups.end {
+            let start = self.group_atoms
+                .get(groups.start)
+                .map_or(self.num_atoms(), |a| a.start);
+            return start..start;
+        }
+        self.group_atoms[groups.start].start..self.group_atoms[groups.end - 1].end
+    }
+}
+
+impl Mmtf {
+    /// Resolve the model, chain, group and atom [`Hierarchy`](../hierarchy/struct.Hierarchy.html)
+    pub fn hierarchy(&self) -> Hierarchy {
+        Hierarchy::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    #[test]
+    fn it_split_sizes_into_ranges() {
+        assert_eq!(vec![0..2, 2..2, 2..5], ranges(vec![2, 0, 3]));
+        assert_eq!(vec![0, 0, 2, 2, 2], parents(&ranges(vec![2, 0, 3])));
+    }
+
+    #[test]
+    fn it_resolve_hierarchy() {
+        let mmtf = load_173d();
+        let hierarchy = mmtf.hierarchy();
+
+        assert_eq!(1, hierarchy.num_models());
+        assert_eq!(8, hierarchy.num_chains());
+        assert_eq!(124, hierarchy.num_groups());
+        assert_eq!(512, hierarchy.num_atoms());
+        assert_eq!(0..512, hierarchy.model_atoms(0));
+        assert_eq!(0..124, hierarchy.model_groups(0));
+
+        // the last four chains only contain waters
+        let waters = hierarchy.chain_atoms(4);
+        assert_eq!(46, waters.len());
+        assert_eq!(4, hierarchy.atom_chain(waters.start));
+        assert_eq!(3, hierarchy.atom_chain(waters.start - 1));
+        assert_eq!(0, hierarchy.atom_model(511));
+    }
+}
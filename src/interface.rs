@@ -0,0 +1,514 @@
+use std::collections::BTreeMap;
+
+use element::Element;
+use hbonds::{HydrogenBond, HydrogenBondCriteria};
+use mmtf::{EntityType, Mmtf};
+use neighbors::NeighborSearch;
+use sasa::ShrakeRupley;
+use selection::AtomSet;
+
+/// Kind of molecule a chain consists of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MoleculeType {
+    /// Chain containing amino acids
+    Protein,
+    /// Chain containing nucleotides
+    NucleicAcid,
+    /// Any other non-water chain, e.g. cofactors, ions and saccharides
+    Ligand,
+    /// Chain of water molecules
+    Water,
+}
+
+/// Settings of the interface analysis, distances in **Å**
+#[derive(Debug, Clone)]
+pub struct InterfaceOptions {
+    /// Largest distance of heavy atoms of two residues in contact
+    pub cutoff: f32,
+
+    /// Criteria of hydrogen bonds between the chains
+    pub hydrogen_bonds: HydrogenBondCriteria,
+
+    /// Largest distance of oppositely charged atoms forming a salt bridge
+    pub salt_bridge_distance: f32,
+
+    /// Whether histidine side chains count as positively charged, as below
+    /// pH 6. Histidine is mostly neutral at pH 7, so this is off by default.
+    pub charged_histidine: bool,
+
+    /// Largest distance of two cysteine SG atoms forming a disulfide bond
+    pub disulfide_distance: f32,
+
+    /// Whether water molecules take part in the analysis
+    pub include_waters: bool,
+
+    /// Whether the buried surface area of each interface is computed
+    pub buried_area: bool,
+}
+
+impl Default for InterfaceOptions {
+    fn default() -> Self {
+        InterfaceOptions {
+            cutoff: 5.0,
+            hydrogen_bonds: HydrogenBondCriteria::default(),
+            salt_bridge_distance: 4.0,
+            charged_histidine: false,
+            disulfide_distance: 2.5,
+            include_waters: false,
+            buried_area: true,
+        }
+    }
+}
+
+/// Pair of atoms of two chains with their distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtomPair {
+    /// Atom of the first and of the second chain
+    pub atoms: (usize, usize),
+
+    /// Distance in **Å**
+    pub distance: f32,
+}
+
+/// Pair of groups of two chains in contact
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResidueContact {
+    /// Group of the first and of the second chain
+    pub groups: (usize, usize),
+
+    /// Smallest distance of their atoms in **Å**
+    pub distance: f32,
+
+    /// Number of atom pairs within the cutoff
+    pub num_atom_pairs: usize,
+}
+
+/// Interface between two chains of the same model
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    /// Chains in contact, the lower index first
+    pub chains: (usize, usize),
+
+    /// Molecule types of the two chains
+    pub types: (MoleculeType, MoleculeType),
+
+    /// Residue pairs within the cutoff
+    pub contacts: Vec<ResidueContact>,
+
+    /// Hydrogen bonds between the chains, donated by either chain
+    pub hydrogen_bonds: Vec<HydrogenBond>,
+
+    /// Oppositely charged side-chain and phosphate atoms
+    pub salt_bridges: Vec<AtomPair>,
+
+    /// Cysteine SG atoms within bonding distance
+    pub disulfides: Vec<AtomPair>,
+
+    /// Surface area in **Å²** buried by the two chains, the sum of the
+    /// accessible areas of the separated chains minus that of the complex
+    pub buried_area: Option<f32>,
+}
+
+impl Interface {
+    /// Whether the interface is formed by molecules of the two given types,
+    /// in either order
+    pub fn is_between(&self, a: MoleculeType, b: MoleculeType) -> bool {
+        self.types == (a, b) || self.types == (b, a)
+    }
+}
+
+/// Interfaces between the chains of two entities
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityInterface {
+    /// Entities in contact, the lower index first
+    pub entities: (usize, usize),
+
+    /// Indices of the chain interfaces of the entity pair
+    pub interfaces: Vec<usize>,
+
+    /// Number of residue contacts of all chain interfaces
+    pub num_contacts: usize,
+
+    /// Number of hydrogen bonds of all chain interfaces
+    pub num_hydrogen_bonds: usize,
+
+    /// Number of salt bridges of all chain interfaces
+    pub num_salt_bridges: usize,
+
+    /// Number of disulfide bonds of all chain interfaces
+    pub num_disulfides: usize,
+
+    /// Total buried surface area in **Å²** of all chain interfaces
+    pub buried_area: Option<f32>,
+}
+
+/// Charge of side-chain and phosphate atoms at neutral pH, histidine is
+/// only charged if `histidine` is set
+fn charge(group_name: &str, atom_name: &str, histidine: bool) -> i8 {
+    match (group_name, atom_name) {
+        ("LYS", "NZ") | ("ARG", "NE") | ("ARG", "NH1") | ("ARG", "NH2") => 1,
+        ("HIS", "ND1") | ("HIS", "NE2") if histidine => 1,
+        ("ASP", "OD1") | ("ASP", "OD2") | ("GLU", "OE1") | ("GLU", "OE2") => -1,
+        (_, "OP1") | (_, "OP2") | (_, "O1P") | (_, "O2P") => -1,
+        _ => 0,
+    }
+}
+
+impl Mmtf {
+    /// Index of the entity of each chain, `None` for chains without entity
+    pub fn chain_entities(&self) -> Vec<Option<usize>> {
+        let mut entities = vec![None; self.chain_id_list.len()];
+        if let Some(ref entity_list) = self.entity_list {
+            for (index, entity) in entity_list.iter().enumerate() {
+                for chain in &entity.chain_index_list {
+                    if let Some(slot) = entities.get_mut(*chain as usize) {
+                        *slot = Some(index);
+                    }
+                }
+            }
+        }
+        entities
+    }
+
+    /// Molecule type of each chain
+    ///
+    /// Water and non-polymer entities are taken from the entity of the chain,
+    /// polymers and chains without entity are classified by their groups.
+    pub fn chain_molecule_types(&self) -> Vec<MoleculeType> {
+        let chain_entities = self.chain_entities();
+        self.hierarchy()
+            .chain_groups
+            .iter()
+            .zip(chain_entities)
+            .map(|(groups, entity)| {
+                let entity =
+                    entity.and_then(|entity| self.entity_list.as_ref().map(|list| &list[entity]));
+                match entity.map(|entity| entity.entity_type()) {
+                    Some(EntityType::Water) => return MoleculeType::Water,
+                    Some(EntityType::NonPolymer) => return MoleculeType::Ligand,
+                    _ => {}
+                }
+
+                let group_types: Vec<_> = self.group_type_list[groups.clone()]
+                    .iter()
+                    .map(|group_type| &self.group_list[*group_type as usize])
+                    .collect();
+                if group_types
+                    .iter()
+                    .any(|group_type| group_type.is_amino_acid())
+                {
+                    MoleculeType::Protein
+                } else if group_types
+                    .iter()
+                    .any(|group_type| group_type.is_nucleotide())
+                {
+                    MoleculeType::NucleicAcid
+                } else if !group_types.is_empty()
+                    && group_types.iter().all(|group_type| group_type.is_water())
+                {
+                    MoleculeType::Water
+                } else {
+                    MoleculeType::Ligand
+                }
+            })
+            .collect()
+    }
+
+    /// Interfaces between all pairs of chains of the same model, ordered by chain pair
+    ///
+    /// Only heavy atoms are considered for contacts, salt bridges and
+    /// disulfide bonds. Hydrogen bonds are detected with the
+    /// [`HydrogenBondCriteria`](../hbonds/struct.HydrogenBondCriteria.html) of the options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    /// use mmtf::interface::{InterfaceOptions, MoleculeType};
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// let interfaces = mmtf.interfaces(&InterfaceOptions::default());
+    ///
+    /// // actinomycin D intercalates into the DNA duplex
+    /// assert!(interfaces
+    ///     .iter()
+    ///     .any(|interface| interface.is_between(MoleculeType::Protein, MoleculeType::NucleicAcid)));
+    /// ```
+    pub fn interfaces(&self, options: &InterfaceOptions) -> Vec<Interface> {
+        let hierarchy = self.hierarchy();
+        let elements = self.atom_elements();
+        let types = self.chain_molecule_types();
+        let atoms = (0..hierarchy.num_atoms()).filter(|atom| {
+            elements[*atom] != Some(Element::H)
+                && (options.include_waters
+                    || types[hierarchy.atom_chain(*atom)] != MoleculeType::Water)
+        });
+        let max_distance = options
+            .cutoff
+            .max(options.salt_bridge_distance)
+            .max(options.disulfide_distance);
+        let search = NeighborSearch::with_atoms(self, atoms, max_distance);
+
+        let mut interfaces: BTreeMap<(usize, usize), Interface> = BTreeMap::new();
+        let mut contacts: BTreeMap<(usize, usize), ResidueContact> = BTreeMap::new();
+        for (a, b, distance) in search.pairs_within(max_distance) {
+            let (chain_a, chain_b) = (hierarchy.atom_chain(a), hierarchy.atom_chain(b));
+            if chain_a == chain_b
+                || hierarchy.chain_model[chain_a] != hierarchy.chain_model[chain_b]
+            {
+                continue;
+            }
+            // atoms are ordered like the chains, since chains are contiguous
+            let interface = interfaces
+                .entry((chain_a, chain_b))
+                .or_insert_with(|| Interface {
+                    chains: (chain_a, chain_b),
+                    types: (types[chain_a], types[chain_b]),
+                    contacts: Vec::new(),
+                    hydrogen_bonds: Vec::new(),
+                    salt_bridges: Vec::new(),
+                    disulfides: Vec::new(),
+                    buried_area: None,
+                });
+            let pair = AtomPair {
+                atoms: (a, b),
+                distance,
+            };
+            let (group_a, group_b) = (hierarchy.atom_group[a], hierarchy.atom_group[b]);
+
+            if distance <= options.cutoff {
+                let contact = contacts
+                    .entry((group_a, group_b))
+                    .or_insert(ResidueContact {
+                        groups: (group_a, group_b),
+                        distance,
+                        num_atom_pairs: 0,
+                    });
+                contact.distance = contact.distance.min(distance);
+                contact.num_atom_pairs += 1;
+            }
+
+            let type_a = &self.group_list[self.group_type_list[group_a] as usize];
+            let type_b = &self.group_list[self.group_type_list[group_b] as usize];
+            let name_a = type_a.atom_name_list[a - hierarchy.group_atoms[group_a].start].as_str();
+            let name_b = type_b.atom_name_list[b - hierarchy.group_atoms[group_b].start].as_str();
+            let histidine = options.charged_histidine;
+            if distance <= options.salt_bridge_distance
+                && charge(&type_a.group_name, name_a, histidine)
+                    * charge(&type_b.group_name, name_b, histidine)
+                    < 0
+            {
+                interface.salt_bridges.push(pair);
+            }
+            if distance <= options.disulfide_distance
+                && (type_a.group_name.as_str(), name_a) == ("CYS", "SG")
+                && (type_b.group_name.as_str(), name_b) == ("CYS", "SG")
+            {
+                interface.disulfides.push(pair);
+            }
+        }
+        for (groups, contact) in contacts {
+            let chains = (
+                hierarchy.group_chain[groups.0],
+                hierarchy.group_chain[groups.1],
+            );
+            if let Some(interface) = interfaces.get_mut(&chains) {
+                interface.contacts.push(contact);
+            }
+        }
+
+        for bond in options.hydrogen_bonds.find(self) {
+            let (a, b) = (
+                hierarchy.atom_chain(bond.donor),
+                hierarchy.atom_chain(bond.acceptor),
+            );
+            if let Some(interface) = interfaces.get_mut(&(a.min(b), a.max(b))) {
+                interface.hydrogen_bonds.push(bond);
+            }
+        }
+
+        let mut interfaces: Vec<Interface> = interfaces
+            .into_values()
+            .filter(|interface| !interface.contacts.is_empty())
+            .collect();
+        if options.buried_area {
+            let sasa = ShrakeRupley {
+                include_waters: options.include_waters,
+                ..Default::default()
+            };
+            let num_atoms = hierarchy.num_atoms();
+            let area = |chains: &[usize]| {
+                let atoms = chains
+                    .iter()
+                    .flat_map(|chain| hierarchy.chain_atoms(*chain));
+                sasa.compute(&self.select(&AtomSet::from_indices(num_atoms, atoms)))
+                    .total()
+            };
+            let mut chain_areas = vec![None; hierarchy.num_chains()];
+            for interface in interfaces.iter_mut() {
+                let (a, b) = interface.chains;
+                let separate: f32 = [a, b]
+                    .iter()
+                    .map(|chain| *chain_areas[*chain].get_or_insert_with(|| area(&[*chain])))
+                    .sum();
+                interface.buried_area = Some(separate - area(&[a, b]));
+            }
+        }
+        interfaces
+    }
+
+    /// Summarize chain interfaces by the entities of their chains, ordered by entity pair
+    ///
+    /// Interfaces of chains without entity are left out. Interfaces between
+    /// chains of the same entity are reported with equal entity indices.
+    pub fn entity_interfaces(&self, interfaces: &[Interface]) -> Vec<EntityInterface> {
+        let chain_entities = self.chain_entities();
+        let mut entities: BTreeMap<(usize, usize), EntityInterface> = BTreeMap::new();
+        for (index, interface) in interfaces.iter().enumerate() {
+            let (a, b) = match (
+                chain_entities[interface.chains.0],
+                chain_entities[interface.chains.1],
+            ) {
+                (Some(a), Some(b)) => (a.min(b), a.max(b)),
+                _ => continue,
+            };
+            let summary = entities.entry((a, b)).or_insert_with(|| EntityInterface {
+                entities: (a, b),
+                interfaces: Vec::new(),
+                num_contacts: 0,
+                num_hydrogen_bonds: 0,
+                num_salt_bridges: 0,
+                num_disulfides: 0,
+                buried_area: Some(0.0),
+            });
+            summary.interfaces.push(index);
+            summary.num_contacts += interface.contacts.len();
+            summary.num_hydrogen_bonds += interface.hydrogen_bonds.len();
+            summary.num_salt_bridges += interface.salt_bridges.len();
+            summary.num_disulfides += interface.disulfides.len();
+            summary.buried_area = match (summary.buried_area, interface.buried_area) {
+                (Some(total), Some(area)) => Some(total + area),
+                _ => None,
+            };
+        }
+        entities.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    #[test]
+    fn it_classify_chains() {
+        let mmtf = load_173d();
+        assert_eq!(
+            vec![
+                Some(0),
+                Some(0),
+                Some(1),
+                Some(1),
+                Some(2),
+                Some(2),
+                Some(2),
+                Some(2)
+            ],
+            mmtf.chain_entities()
+        );
+        let types = mmtf.chain_molecule_types();
+        assert_eq!(MoleculeType::NucleicAcid, types[0]);
+        assert_eq!(MoleculeType::Protein, types[2]);
+        assert_eq!(MoleculeType::Water, types[4]);
+    }
+
+    #[test]
+    fn it_charge_histidine_on_request() {
+        assert_eq!(1, charge("LYS", "NZ", false));
+        assert_eq!(-1, charge("GLU", "OE2", false));
+        assert_eq!(0, charge("HIS", "NE2", false));
+        assert_eq!(1, charge("HIS", "NE2", true));
+        assert_eq!(0, charge("HIS", "CA", true));
+    }
+
+    #[test]
+    fn it_find_chain_interfaces() {
+        let mmtf = load_173d();
+        let interfaces = mmtf.interfaces(&InterfaceOptions::default());
+        let hierarchy = mmtf.hierarchy();
+
+        // each actinomycin D binds to one of the DNA strands
+        let chains: Vec<_> = interfaces
+            .iter()
+            .map(|interface| interface.chains)
+            .collect();
+        assert_eq!(vec![(0, 2), (1, 3)], chains);
+        let complex = &interfaces[0];
+        assert_eq!(
+            (MoleculeType::NucleicAcid, MoleculeType::Protein),
+            complex.types
+        );
+        assert!(complex.is_between(MoleculeType::Protein, MoleculeType::NucleicAcid));
+        assert!(!complex.hydrogen_bonds.is_empty());
+        assert!(complex.buried_area.is_some_and(|area| area > 0.0));
+
+        for interface in &interfaces {
+            assert!(interface.chains.0 < interface.chains.1);
+            // waters are excluded by default
+            assert!(interface.chains.1 < 4);
+            for contact in &interface.contacts {
+                assert!(contact.distance <= 5.0);
+                assert_eq!(interface.chains.0, hierarchy.group_chain[contact.groups.0]);
+                assert_eq!(interface.chains.1, hierarchy.group_chain[contact.groups.1]);
+            }
+            for bond in &interface.hydrogen_bonds {
+                assert!(bond.distance <= 3.5);
+                let mut chains = [
+                    hierarchy.atom_chain(bond.donor),
+                    hierarchy.atom_chain(bond.acceptor),
+                ];
+                chains.sort();
+                assert_eq!([interface.chains.0, interface.chains.1], chains);
+            }
+            assert!(interface.disulfides.is_empty());
+        }
+
+        let with_waters = mmtf.interfaces(&InterfaceOptions {
+            include_waters: true,
+            buried_area: false,
+            ..Default::default()
+        });
+        assert!(with_waters
+            .iter()
+            .any(|interface| interface.types.1 == MoleculeType::Water));
+        assert!(with_waters
+            .iter()
+            .all(|interface| interface.buried_area.is_none()));
+    }
+
+    #[test]
+    fn it_summarize_entity_interfaces() {
+        let mmtf = load_173d();
+        let interfaces = mmtf.interfaces(&InterfaceOptions::default());
+        let entities = mmtf.entity_interfaces(&interfaces);
+
+        assert_eq!(1, entities.len());
+        let dna_drug = &entities[0];
+        assert_eq!((0, 1), dna_drug.entities);
+        assert_eq!(vec![0, 1], dna_drug.interfaces);
+        let contacts: usize = dna_drug
+            .interfaces
+            .iter()
+            .map(|index| interfaces[*index].contacts.len())
+            .sum();
+        assert_eq!(contacts, dna_drug.num_contacts);
+        assert!(dna_drug.buried_area.is_some_and(|area| area > 0.0));
+    }
+}
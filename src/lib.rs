@@ -29,15 +29,61 @@ extern crate num_integer;
 extern crate num_traits;
 extern crate rmp_serde as rmps;
 extern crate serde;
-extern crate serde_bytes;
 #[macro_use]
 extern crate serde_derive;
 
+pub mod altloc;
+pub mod alignment;
+pub mod approx;
 pub mod binary_decoder;
+pub mod bonds;
+pub mod chain;
 pub mod encoding;
 pub mod codec;
 pub mod encode;
+pub mod fasta;
+pub mod geometry;
+pub mod hbonds;
+pub mod hierarchy;
+pub mod interface;
+pub mod ligand;
 pub mod decode;
+pub mod diff;
+pub mod dihedrals;
+pub mod element;
 pub mod mmtf;
+pub mod model;
+pub mod molfile;
+pub mod neighbors;
+pub mod residue;
+pub mod sasa;
+pub mod selection;
+pub mod stream;
+pub mod subset;
+pub mod superposition;
+pub mod transform;
 
+pub use diff::diff;
 pub use mmtf::Mmtf;
+
+#[cfg(test)]
+mod test_support {
+    use std::fs::File;
+    use std::path::Path;
+
+    use mmtf::Mmtf;
+
+    /// Open the `173D` structure of the test data
+    pub(crate) fn open_173d() -> File {
+        let file_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("data")
+            .join("173D.mmtf");
+        File::open(&file_path).unwrap()
+    }
+
+    /// Decode the `173D` structure of the test data
+    pub(crate) fn load_173d() -> Mmtf {
+        Mmtf::from(open_173d()).unwrap()
+    }
+}
@@ -0,0 +1,227 @@
+use std::ops::Range;
+
+use bonds::Bond;
+use mmtf::{EntityType, Mmtf};
+use neighbors::NeighborSearch;
+use selection::AtomSet;
+
+/// A ligand group of a structure
+///
+/// Bonds are given with global atom indices, see [`Bond`](../bonds/struct.Bond.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ligand {
+    /// Index of the group
+    pub group: usize,
+
+    /// Index of the chain containing the group
+    pub chain: usize,
+
+    /// Chemical component name of the group, e.g. `"HEM"`
+    pub name: String,
+
+    /// Range of atom indices of the group
+    pub atoms: Range<usize>,
+
+    /// Bonds between atoms of the group
+    pub bonds: Vec<Bond>,
+
+    /// Bonds between an atom of the group and an atom of another group,
+    /// e.g. covalent attachments to the polymer
+    pub inter_group_bonds: Vec<Bond>,
+}
+
+/// Residues surrounding a ligand
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindingSite {
+    /// Group of the ligand
+    pub ligand: usize,
+
+    /// Groups with any atom within the cutoff of any ligand atom, in ascending order
+    pub groups: Vec<usize>,
+
+    /// All atoms of the binding site groups, to be extracted with
+    /// [`Mmtf::select`](../mmtf/struct.Mmtf.html#method.select)
+    pub atoms: AtomSet,
+}
+
+impl Mmtf {
+    /// All ligand groups of the structure
+    ///
+    /// Groups of non-polymer entities are ligands, as are groups of other
+    /// entities whose chemical component is neither an amino acid, a
+    /// nucleotide nor water (see [`GroupType::is_ligand`](../mmtf/struct.GroupType.html#method.is_ligand)).
+    /// Waters are never ligands.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// let ligands = mmtf.ligands();
+    ///
+    /// // the phenoxazone chromophores of actinomycin D
+    /// assert_eq!(2, ligands.len());
+    /// assert_eq!("PXZ", ligands[0].name);
+    ///
+    /// let site = mmtf.binding_site(&ligands[0], 4.0);
+    /// let pocket = mmtf.select(&site.atoms);
+    /// assert_eq!(site.groups.len(), pocket.num_groups as usize);
+    /// ```
+    pub fn ligands(&self) -> Vec<Ligand> {
+        let hierarchy = self.hierarchy();
+        let entity_types: Vec<Option<EntityType>> = self
+            .chain_entities()
+            .into_iter()
+            .map(|entity| {
+                entity.and_then(|entity| {
+                    self.entity_list
+                        .as_ref()
+                        .map(|list| list[entity].entity_type())
+                })
+            })
+            .collect();
+
+        let mut ligands: Vec<Ligand> = (0..hierarchy.num_groups())
+            .filter_map(|group| {
+                let group_type = &self.group_list[self.group_type_list[group] as usize];
+                let chain = hierarchy.group_chain[group];
+                let is_ligand = match entity_types[chain] {
+                    Some(EntityType::Water) => false,
+                    Some(EntityType::NonPolymer) => !group_type.is_water(),
+                    _ => group_type.is_ligand(),
+                };
+                if !is_ligand {
+                    return None;
+                }
+                Some(Ligand {
+                    group,
+                    chain,
+                    name: group_type.group_name.clone(),
+                    atoms: hierarchy.group_atoms[group].clone(),
+                    bonds: Vec::new(),
+                    inter_group_bonds: Vec::new(),
+                })
+            })
+            .collect();
+        if ligands.is_empty() {
+            return ligands;
+        }
+
+        let mut atom_ligand = vec![None; hierarchy.num_atoms()];
+        for (index, ligand) in ligands.iter().enumerate() {
+            for atom in ligand.atoms.clone() {
+                atom_ligand[atom] = Some(index);
+            }
+        }
+        for bond in self.bond_graph().bonds() {
+            match (atom_ligand[bond.atom1], atom_ligand[bond.atom2]) {
+                (Some(a), Some(b)) if a == b => ligands[a].bonds.push(*bond),
+                (a, b) => {
+                    for index in a.into_iter().chain(b) {
+                        ligands[index].inter_group_bonds.push(*bond);
+                    }
+                }
+            }
+        }
+        ligands
+    }
+
+    /// Binding site of the ligand, the groups of the same model with any
+    /// heavy or hydrogen atom within `cutoff` **Å** of any ligand atom
+    ///
+    /// The ligand group itself and waters are not part of the binding site.
+    pub fn binding_site(&self, ligand: &Ligand, cutoff: f32) -> BindingSite {
+        let hierarchy = self.hierarchy();
+        let model = hierarchy.chain_model[ligand.chain];
+        let search = NeighborSearch::model(self, model, cutoff);
+
+        let mut groups: Vec<usize> = ligand
+            .atoms
+            .clone()
+            .flat_map(|atom| search.within(self.position(atom), cutoff))
+            .map(|(atom, _)| hierarchy.atom_group[atom])
+            .filter(|group| {
+                *group != ligand.group
+                    && !self.group_list[self.group_type_list[*group] as usize].is_water()
+            })
+            .collect();
+        groups.sort();
+        groups.dedup();
+
+        let atoms = AtomSet::from_indices(
+            hierarchy.num_atoms(),
+            groups
+                .iter()
+                .flat_map(|group| hierarchy.group_atoms[*group].clone()),
+        );
+        BindingSite {
+            ligand: ligand.group,
+            groups,
+            atoms,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_support::load_173d;
+
+    #[test]
+    fn it_find_ligands_with_bonds() {
+        let mmtf = load_173d();
+        let ligands = mmtf.ligands();
+
+        assert_eq!(
+            vec![21, 22],
+            ligands.iter().map(|l| l.group).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![2, 3],
+            ligands.iter().map(|l| l.chain).collect::<Vec<_>>()
+        );
+
+        let ligand = &ligands[0];
+        assert_eq!(13, ligand.bonds.len());
+        assert!(ligand
+            .bonds
+            .iter()
+            .all(|bond| ligand.atoms.contains(&bond.atom1) && ligand.atoms.contains(&bond.atom2)));
+        // the chromophore is attached to the threonine of the peptide
+        assert!(!ligand.inter_group_bonds.is_empty());
+        assert!(ligand.inter_group_bonds.iter().all(|bond| {
+            ligand.atoms.contains(&bond.atom1) != ligand.atoms.contains(&bond.atom2)
+        }));
+    }
+
+    #[test]
+    fn it_extract_binding_site() {
+        let mmtf = load_173d();
+        let ligand = &mmtf.ligands()[0];
+        let site = mmtf.binding_site(ligand, 4.0);
+        let hierarchy = mmtf.hierarchy();
+
+        assert!(!site.groups.contains(&ligand.group));
+        // the chromophore intercalates into the DNA
+        assert!(site.groups.iter().any(|group| *group < 16));
+        assert!(site.groups.iter().all(|group| *group < 28));
+        assert!(site.groups.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let atoms: usize = site
+            .groups
+            .iter()
+            .map(|group| hierarchy.group_atoms[*group].len())
+            .sum();
+        assert_eq!(atoms, site.atoms.len());
+
+        let wide = mmtf.binding_site(ligand, 8.0);
+        assert!(wide.groups.len() > site.groups.len());
+    }
+}
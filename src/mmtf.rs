@@ -3,6 +3,7 @@ use super::decode;
 use serde::{Deserialize, Serialize};
 use rmps::Deserializer;
 use rmps::decode::Error;
+use std::fmt;
 use std::io::Read;
 
 /// Transform List
@@ -10,7 +11,7 @@ use std::io::Read;
 /// Instructions on how to transform coordinates for an array
 /// of chains to create (biological) assemblies.
 /// The translational component is given in **Å**.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Transform {
     /// Pointers into chain data fields
@@ -20,7 +21,7 @@ pub struct Transform {
 }
 
 /// Bio Assembly
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BioAssembly {
     /// Array of transform objects
@@ -43,7 +44,7 @@ pub struct BioAssembly {
 /// entries of the [`Mmtf.sequence_index_list`](struct.Mmtf.html#structfield.sequence_index_list) field.
 /// Further, characters follow the IUPAC single letter code for protein
 /// or *DNA/RNA* residues, otherwise the character 'X'.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Entity {
     /// Pointers into chain data fields
@@ -67,7 +68,7 @@ pub struct Entity {
 /// there are two or more entries given that have the same sequence
 /// index, group id (and insertion code) but are of a different group
 /// type. The defining property is their identical sequence index.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GroupType {
     /// `Vec` of formal charges
@@ -97,8 +98,247 @@ pub struct GroupType {
     pub chem_comp_type: String,
 }
 
+macro_rules! string_enum {
+    ($(#[$meta:meta])* pub enum $name:ident { $($(#[$vmeta:meta])* $variant:ident => $value:expr,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$vmeta])* $variant,)*
+            /// Any value not covered by the other variants
+            Other(String),
+        }
+
+        impl $name {
+            /// The value as stored in the MMTF file
+            pub fn as_str(&self) -> &str {
+                match *self {
+                    $($name::$variant => $value,)*
+                    $name::Other(ref value) => value,
+                }
+            }
+        }
+
+        impl<'a> From<&'a str> for $name {
+            fn from(value: &'a str) -> $name {
+                let trimmed = value.trim();
+                $(
+                    if trimmed.eq_ignore_ascii_case($value) {
+                        return $name::$variant;
+                    }
+                )*
+                $name::Other(value.to_string())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.as_str())
+            }
+        }
+    }
+}
+
+string_enum! {
+    /// Type of an [`Entity`](Entity), as given by its `_type` field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mmtf::mmtf::EntityType;
+    ///
+    /// assert_eq!(EntityType::NonPolymer, EntityType::from("non-polymer"));
+    /// assert_eq!(EntityType::Other("foo".to_string()), EntityType::from("foo"));
+    /// ```
+    pub enum EntityType {
+        /// `polymer`
+        Polymer => "polymer",
+        /// `non-polymer`
+        NonPolymer => "non-polymer",
+        /// `water`
+        Water => "water",
+        /// `macrolide`
+        Macrolide => "macrolide",
+        /// `branched`
+        Branched => "branched",
+    }
+}
+
+string_enum! {
+    /// Chemical component type of a [`GroupType`](GroupType), following
+    /// the `_chem_comp.type` values of the `mmCIF` dictionary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mmtf::mmtf::ChemCompType;
+    ///
+    /// let comp = ChemCompType::from("L-PEPTIDE LINKING");
+    /// assert_eq!(ChemCompType::LPeptideLinking, comp);
+    /// assert!(comp.is_peptide());
+    /// ```
+    pub enum ChemCompType {
+        /// `PEPTIDE LINKING`
+        PeptideLinking => "PEPTIDE LINKING",
+        /// `PEPTIDE-LIKE`
+        PeptideLike => "PEPTIDE-LIKE",
+        /// `L-PEPTIDE LINKING`
+        LPeptideLinking => "L-PEPTIDE LINKING",
+        /// `L-PEPTIDE NH3 AMINO TERMINUS`
+        LPeptideAminoTerminus => "L-PEPTIDE NH3 AMINO TERMINUS",
+        /// `L-PEPTIDE COOH CARBOXY TERMINUS`
+        LPeptideCarboxyTerminus => "L-PEPTIDE COOH CARBOXY TERMINUS",
+        /// `L-BETA-PEPTIDE, C-GAMMA LINKING`
+        LBetaPeptideLinking => "L-BETA-PEPTIDE, C-GAMMA LINKING",
+        /// `L-GAMMA-PEPTIDE, C-DELTA LINKING`
+        LGammaPeptideLinking => "L-GAMMA-PEPTIDE, C-DELTA LINKING",
+        /// `D-PEPTIDE LINKING`
+        DPeptideLinking => "D-PEPTIDE LINKING",
+        /// `D-PEPTIDE NH3 AMINO TERMINUS`
+        DPeptideAminoTerminus => "D-PEPTIDE NH3 AMINO TERMINUS",
+        /// `D-PEPTIDE COOH CARBOXY TERMINUS`
+        DPeptideCarboxyTerminus => "D-PEPTIDE COOH CARBOXY TERMINUS",
+        /// `D-BETA-PEPTIDE, C-GAMMA LINKING`
+        DBetaPeptideLinking => "D-BETA-PEPTIDE, C-GAMMA LINKING",
+        /// `D-GAMMA-PEPTIDE, C-DELTA LINKING`
+        DGammaPeptideLinking => "D-GAMMA-PEPTIDE, C-DELTA LINKING",
+        /// `DNA LINKING`
+        DnaLinking => "DNA LINKING",
+        /// `L-DNA LINKING`
+        LDnaLinking => "L-DNA LINKING",
+        /// `DNA OH 5 PRIME TERMINUS`
+        DnaFivePrimeTerminus => "DNA OH 5 PRIME TERMINUS",
+        /// `DNA OH 3 PRIME TERMINUS`
+        DnaThreePrimeTerminus => "DNA OH 3 PRIME TERMINUS",
+        /// `RNA LINKING`
+        RnaLinking => "RNA LINKING",
+        /// `L-RNA LINKING`
+        LRnaLinking => "L-RNA LINKING",
+        /// `RNA OH 5 PRIME TERMINUS`
+        RnaFivePrimeTerminus => "RNA OH 5 PRIME TERMINUS",
+        /// `RNA OH 3 PRIME TERMINUS`
+        RnaThreePrimeTerminus => "RNA OH 3 PRIME TERMINUS",
+        /// `SACCHARIDE`
+        Saccharide => "SACCHARIDE",
+        /// `D-SACCHARIDE`
+        DSaccharide => "D-SACCHARIDE",
+        /// `D-SACCHARIDE, ALPHA LINKING`
+        DSaccharideAlphaLinking => "D-SACCHARIDE, ALPHA LINKING",
+        /// `D-SACCHARIDE, BETA LINKING`
+        DSaccharideBetaLinking => "D-SACCHARIDE, BETA LINKING",
+        /// `L-SACCHARIDE`
+        LSaccharide => "L-SACCHARIDE",
+        /// `L-SACCHARIDE, ALPHA LINKING`
+        LSaccharideAlphaLinking => "L-SACCHARIDE, ALPHA LINKING",
+        /// `L-SACCHARIDE, BETA LINKING`
+        LSaccharideBetaLinking => "L-SACCHARIDE, BETA LINKING",
+        /// `NON-POLYMER`
+        NonPolymer => "NON-POLYMER",
+    }
+}
+
+impl ChemCompType {
+    /// `true` for all peptide (amino acid) component types
+    pub fn is_peptide(&self) -> bool {
+        matches!(
+            *self,
+            ChemCompType::PeptideLinking
+                | ChemCompType::PeptideLike
+                | ChemCompType::LPeptideLinking
+                | ChemCompType::LPeptideAminoTerminus
+                | ChemCompType::LPeptideCarboxyTerminus
+                | ChemCompType::LBetaPeptideLinking
+                | ChemCompType::LGammaPeptideLinking
+                | ChemCompType::DPeptideLinking
+                | ChemCompType::DPeptideAminoTerminus
+                | ChemCompType::DPeptideCarboxyTerminus
+                | ChemCompType::DBetaPeptideLinking
+                | ChemCompType::DGammaPeptideLinking
+        )
+    }
+
+    /// `true` for all DNA component types
+    pub fn is_dna(&self) -> bool {
+        matches!(
+            *self,
+            ChemCompType::DnaLinking
+                | ChemCompType::LDnaLinking
+                | ChemCompType::DnaFivePrimeTerminus
+                | ChemCompType::DnaThreePrimeTerminus
+        )
+    }
+
+    /// `true` for all RNA component types
+    pub fn is_rna(&self) -> bool {
+        matches!(
+            *self,
+            ChemCompType::RnaLinking
+                | ChemCompType::LRnaLinking
+                | ChemCompType::RnaFivePrimeTerminus
+                | ChemCompType::RnaThreePrimeTerminus
+        )
+    }
+
+    /// `true` for all DNA and RNA component types
+    pub fn is_nucleotide(&self) -> bool {
+        self.is_dna() || self.is_rna()
+    }
+
+    /// `true` for all saccharide component types
+    pub fn is_saccharide(&self) -> bool {
+        matches!(
+            *self,
+            ChemCompType::Saccharide
+                | ChemCompType::DSaccharide
+                | ChemCompType::DSaccharideAlphaLinking
+                | ChemCompType::DSaccharideBetaLinking
+                | ChemCompType::LSaccharide
+                | ChemCompType::LSaccharideAlphaLinking
+                | ChemCompType::LSaccharideBetaLinking
+        )
+    }
+}
+
+/// Group names used for water molecules
+const WATER_NAMES: [&str; 5] = ["HOH", "WAT", "H2O", "DOD", "D2O"];
+
+impl Entity {
+    /// Typed version of the [`_type`](#structfield._type) field
+    pub fn entity_type(&self) -> EntityType {
+        EntityType::from(&self._type[..])
+    }
+}
+
+impl GroupType {
+    /// Typed version of the [`chem_comp_type`](#structfield.chem_comp_type) field
+    pub fn chem_comp(&self) -> ChemCompType {
+        ChemCompType::from(&self.chem_comp_type[..])
+    }
+
+    /// `true` if the group is an amino acid residue
+    pub fn is_amino_acid(&self) -> bool {
+        self.chem_comp().is_peptide()
+    }
+
+    /// `true` if the group is a DNA or RNA nucleotide
+    pub fn is_nucleotide(&self) -> bool {
+        self.chem_comp().is_nucleotide()
+    }
+
+    /// `true` if the group is a water molecule
+    pub fn is_water(&self) -> bool {
+        let name = self.group_name.trim().to_uppercase();
+        WATER_NAMES.iter().any(|w| *w == name)
+    }
+
+    /// `true` if the group is neither an amino acid, a nucleotide
+    /// nor water, e.g. cofactors, ions, saccharides and drug molecules
+    pub fn is_ligand(&self) -> bool {
+        !self.is_amino_acid() && !self.is_nucleotide() && !self.is_water()
+    }
+}
+
 /// MMTF Fields
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Mmtf {
     /// The version number of the specification the file adheres to.
@@ -335,3 +575,70 @@ impl Mmtf {
         Ok(mmtf)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    #[test]
+    fn it_parse_entity_type() {
+        assert_eq!(EntityType::Polymer, EntityType::from("polymer"));
+        assert_eq!(EntityType::Water, EntityType::from("WATER"));
+        assert_eq!(EntityType::Branched, EntityType::from(" branched "));
+        assert_eq!(
+            EntityType::Other("unknown".to_string()),
+            EntityType::from("unknown")
+        );
+        assert_eq!("non-polymer", EntityType::NonPolymer.as_str());
+        assert_eq!("unknown", EntityType::from("unknown").to_string());
+    }
+
+    #[test]
+    fn it_parse_chem_comp_type() {
+        let comp = ChemCompType::from("D-PEPTIDE LINKING");
+        assert_eq!(ChemCompType::DPeptideLinking, comp);
+        assert!(comp.is_peptide());
+        assert!(!comp.is_nucleotide());
+
+        let comp = ChemCompType::from("rna linking");
+        assert!(comp.is_rna() && comp.is_nucleotide() && !comp.is_dna());
+
+        assert!(ChemCompType::from("D-SACCHARIDE, BETA LINKING").is_saccharide());
+        assert_eq!(
+            ChemCompType::Other("OTHER".to_string()),
+            ChemCompType::from("OTHER")
+        );
+    }
+
+    #[test]
+    fn it_classify_group_types() {
+        let mmtf = load_173d();
+        let names = |f: fn(&GroupType) -> bool| -> Vec<&str> {
+            mmtf.group_list
+                .iter()
+                .filter(|g| f(g))
+                .map(|g| &g.group_name[..])
+                .collect()
+        };
+
+        assert_eq!(vec!["SAR", "PRO", "THR", "DVA", "MVA"], names(GroupType::is_amino_acid));
+        assert_eq!(vec!["DG", "DC", "DG", "DA", "DT"], names(GroupType::is_nucleotide));
+        assert_eq!(vec!["HOH"], names(GroupType::is_water));
+        assert_eq!(vec!["PXZ"], names(GroupType::is_ligand));
+    }
+
+    #[test]
+    fn it_classify_entities() {
+        let mmtf = load_173d();
+        let types: Vec<EntityType> = mmtf.entity_list
+            .unwrap()
+            .iter()
+            .map(Entity::entity_type)
+            .collect();
+        assert_eq!(
+            vec![EntityType::Polymer, EntityType::Polymer, EntityType::Water],
+            types
+        );
+    }
+}
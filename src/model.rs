@@ -0,0 +1,278 @@
+use std::fmt;
+
+use bonds::count_bonds;
+use mmtf::Mmtf;
+use selection::AtomSet;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelError {
+    Empty,
+    MultipleModels(String),
+    Topology(String),
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ModelError::Empty => write!(f, "No structure given"),
+            ModelError::MultipleModels(ref err) => {
+                write!(f, "Expected a single model structure: `{}`", err)
+            }
+            ModelError::Topology(ref err) => write!(f, "Incompatible topology: `{}`", err),
+        }
+    }
+}
+
+/// Concatenate optional lists, the result is only given if all lists are
+fn concat<T: Clone>(lists: &[Option<&Vec<T>>]) -> Option<Vec<T>> {
+    let mut result = Vec::new();
+    for list in lists {
+        result.extend_from_slice(list.as_ref()?);
+    }
+    Some(result)
+}
+
+/// Check that `other` has the same chains, groups and atoms as `first`
+fn check_topology(first: &Mmtf, other: &Mmtf, index: usize) -> Result<(), ModelError> {
+    if first.chain_id_list != other.chain_id_list
+        || first.groups_per_chain != other.groups_per_chain
+    {
+        return Err(ModelError::Topology(format!(
+            "chains of structure {} differ",
+            index
+        )));
+    }
+    let groups = first.group_type_list.iter().zip(&other.group_type_list);
+    for (group, (a, b)) in groups.enumerate() {
+        let a = &first.group_list[*a as usize];
+        let b = &other.group_list[*b as usize];
+        if a.group_name != b.group_name || a.atom_name_list != b.atom_name_list {
+            return Err(ModelError::Topology(format!(
+                "group {} of structure {} differs",
+                group, index
+            )));
+        }
+    }
+    Ok(())
+}
+
+impl Mmtf {
+    /// Extract the model with the given index as a standalone structure,
+    /// `None` if there is no such model
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// let ensemble = Mmtf::from_models(&[mmtf.clone(), mmtf.clone()]).unwrap();
+    ///
+    /// assert_eq!(2, ensemble.num_models);
+    /// assert_eq!(mmtf.num_atoms, ensemble.model(1).unwrap().num_atoms);
+    /// assert!(ensemble.model(2).is_none());
+    /// ```
+    pub fn model(&self, index: usize) -> Option<Mmtf> {
+        if index >= self.chains_per_model.len() {
+            return None;
+        }
+        let hierarchy = self.hierarchy();
+        let atoms = hierarchy.model_atoms(index);
+        let selected = AtomSet::from_fn(hierarchy.num_atoms(), |atom| atoms.contains(&atom));
+        Some(self.select(&selected))
+    }
+
+    /// Split the structure into one standalone structure per model
+    pub fn models_split(&self) -> Vec<Mmtf> {
+        (0..self.chains_per_model.len())
+            .filter_map(|index| self.model(index))
+            .collect()
+    }
+
+    /// Merge single model structures with the same topology into one
+    /// multi-model structure.
+    ///
+    /// All structures need the same chains, and the same groups with the same
+    /// atom names. Group types, bonds, entities and bio assemblies are taken from
+    /// the first structure, while coordinates and other per-atom, per-group and
+    /// per-chain data are taken from each model. The chains of entities and bio
+    /// assembly transforms are repeated for every model.
+    pub fn from_models(models: &[Mmtf]) -> Result<Mmtf, ModelError> {
+        let first = models.first().ok_or(ModelError::Empty)?;
+        for (index, model) in models.iter().enumerate() {
+            if model.chains_per_model.len() != 1 {
+                return Err(ModelError::MultipleModels(format!(
+                    "structure {} has {} models",
+                    index,
+                    model.chains_per_model.len()
+                )));
+            }
+            check_topology(first, model, index)?;
+        }
+
+        let num_atoms = first.x_coord_list.len() as i32;
+        let num_chains = first.chain_id_list.len() as i32;
+        let mut mmtf = first.clone();
+
+        mmtf.bond_atom_list = (0..models.len() as i32)
+            .flat_map(|model| {
+                first
+                    .bond_atom_list
+                    .iter()
+                    .map(move |atom| atom + model * num_atoms)
+            })
+            .collect();
+        mmtf.bond_order_list = first
+            .bond_order_list
+            .as_ref()
+            .map(|orders| orders.repeat(models.len()));
+        mmtf.group_type_list = first.group_type_list.repeat(models.len());
+
+        let repeat_chains = |chains: &[i32]| -> Vec<i32> {
+            (0..models.len() as i32)
+                .flat_map(|model| chains.iter().map(move |chain| chain + model * num_chains))
+                .collect()
+        };
+        if let Some(ref mut entities) = mmtf.entity_list {
+            for entity in entities.iter_mut() {
+                entity.chain_index_list = repeat_chains(&entity.chain_index_list);
+            }
+        }
+        if let Some(ref mut assemblies) = mmtf.bio_assembly_list {
+            for transform in assemblies
+                .iter_mut()
+                .flat_map(|assembly| assembly.transform_list.iter_mut())
+            {
+                transform.chain_index_list = repeat_chains(&transform.chain_index_list);
+            }
+        }
+
+        macro_rules! required {
+            ($field:ident) => {
+                models
+                    .iter()
+                    .flat_map(|model| model.$field.iter().cloned())
+                    .collect()
+            };
+        }
+        macro_rules! optional {
+            ($field:ident) => {
+                concat(
+                    &models
+                        .iter()
+                        .map(|model| model.$field.as_ref())
+                        .collect::<Vec<_>>(),
+                )
+            };
+        }
+
+        mmtf.x_coord_list = required!(x_coord_list);
+        mmtf.y_coord_list = required!(y_coord_list);
+        mmtf.z_coord_list = required!(z_coord_list);
+        mmtf.b_factor_list = optional!(b_factor_list);
+        mmtf.atom_id_list = optional!(atom_id_list);
+        mmtf.alt_loc_list = optional!(alt_loc_list);
+        mmtf.occupancy_list = optional!(occupancy_list);
+        mmtf.group_id_list = required!(group_id_list);
+        mmtf.ins_code_list = optional!(ins_code_list);
+        mmtf.sequence_index_list = optional!(sequence_index_list);
+        mmtf.chain_id_list = required!(chain_id_list);
+        mmtf.chain_name_list = optional!(chain_name_list);
+        mmtf.groups_per_chain = required!(groups_per_chain);
+        mmtf.chains_per_model = required!(chains_per_model);
+        let sec_struct: Vec<_> = models.iter().map(Mmtf::sec_struct_per_group).collect();
+        mmtf.sec_struct_list = concat(&sec_struct.iter().map(Option::as_ref).collect::<Vec<_>>());
+
+        mmtf.num_atoms = mmtf.x_coord_list.len() as i32;
+        mmtf.num_groups = mmtf.group_type_list.len() as i32;
+        mmtf.num_chains = mmtf.chain_id_list.len() as i32;
+        mmtf.num_models = mmtf.chains_per_model.len() as i32;
+        mmtf.num_bonds = count_bonds(&mmtf);
+        Ok(mmtf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    fn load_ensemble() -> Mmtf {
+        let first = load_173d();
+        let mut second = first.clone();
+        for x in second.x_coord_list.iter_mut() {
+            *x += 10.0;
+        }
+        Mmtf::from_models(&[first, second]).unwrap()
+    }
+
+    #[test]
+    fn it_merge_models() {
+        let ensemble = load_ensemble();
+
+        assert_eq!(2, ensemble.num_models);
+        assert_eq!(vec![8, 8], ensemble.chains_per_model);
+        assert_eq!(16, ensemble.num_chains);
+        assert_eq!(248, ensemble.num_groups);
+        assert_eq!(1024, ensemble.num_atoms);
+        assert_eq!(2 * 458, ensemble.num_bonds);
+        assert_eq!(248, ensemble.sec_struct_list.as_ref().unwrap().len());
+
+        let entities = ensemble.entity_list.as_ref().unwrap();
+        assert_eq!(vec![0, 1, 8, 9], entities[0].chain_index_list);
+        let transforms = &ensemble.bio_assembly_list.as_ref().unwrap()[0].transform_list;
+        assert_eq!(
+            vec![1, 3, 5, 7, 9, 11, 13, 15],
+            transforms[0].chain_index_list
+        );
+        assert_eq!(ensemble.x_coord_list[0] + 10.0, ensemble.x_coord_list[512]);
+    }
+
+    #[test]
+    fn it_split_models() {
+        let mmtf = load_173d();
+        let models = load_ensemble().models_split();
+
+        assert_eq!(2, models.len());
+        for model in &models {
+            assert_eq!(1, model.num_models);
+            assert_eq!(mmtf.num_atoms, model.num_atoms);
+            assert_eq!(mmtf.num_bonds, model.num_bonds);
+            assert_eq!(mmtf.chains_per_model, model.chains_per_model);
+            assert_eq!(mmtf.sec_struct_list, model.sec_struct_list);
+        }
+        assert_eq!(mmtf.x_coord_list, models[0].x_coord_list);
+        assert_eq!(mmtf.x_coord_list[0] + 10.0, models[1].x_coord_list[0]);
+        assert_eq!(
+            vec![0, 1],
+            models[1].entity_list.as_ref().unwrap()[0].chain_index_list
+        );
+        assert_eq!(mmtf.bio_assembly_list, models[1].bio_assembly_list);
+        assert!(load_ensemble().model(2).is_none());
+    }
+
+    #[test]
+    fn it_reject_incompatible_models() {
+        let mmtf = load_173d();
+        assert_eq!(Err(ModelError::Empty), Mmtf::from_models(&[]).map(|_| ()));
+
+        let ensemble = load_ensemble();
+        match Mmtf::from_models(&[mmtf.clone(), ensemble]) {
+            Err(ModelError::MultipleModels(_)) => (),
+            _ => panic!("multi-model input should be rejected"),
+        }
+
+        let water = mmtf.select(&mmtf.select_atoms("water").unwrap());
+        match Mmtf::from_models(&[mmtf, water]) {
+            Err(ModelError::Topology(_)) => (),
+            _ => panic!("different chains should be rejected"),
+        }
+    }
+}
@@ -0,0 +1,461 @@
+use std::io::{self, Write};
+
+use element::Element;
+use ligand::Ligand;
+use mmtf::Mmtf;
+
+/// Program name written to the header of SDF records
+const PROGRAM: &str = "mmtf-rs";
+
+/// Version of the connection table written by [`Mmtf::write_sdf`](../mmtf/struct.Mmtf.html#method.write_sdf)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SdfVersion {
+    /// Fixed column format, limited to 999 atoms and bonds per record
+    #[default]
+    V2000,
+
+    /// Extended format without size limits
+    V3000,
+}
+
+/// Atom of a ligand record
+struct Atom {
+    name: String,
+    element: Option<Element>,
+    position: [f32; 3],
+    charge: i32,
+}
+
+/// Atoms and bonds of a ligand, bonds with 1-based atom numbers of the record
+struct Molecule {
+    name: String,
+    group_name: String,
+    atoms: Vec<Atom>,
+    bonds: Vec<(usize, usize, Option<i32>)>,
+}
+
+impl Molecule {
+    fn new(mmtf: &Mmtf, ligand: &Ligand) -> Self {
+        let group_type = &mmtf.group_list[mmtf.group_type_list[ligand.group] as usize];
+        let elements = group_type.elements();
+        let atoms = ligand
+            .atoms
+            .clone()
+            .enumerate()
+            .map(|(index, atom)| Atom {
+                name: group_type.atom_name_list[index].clone(),
+                element: elements[index],
+                position: mmtf.position(atom),
+                charge: group_type
+                    .formal_charge_list
+                    .get(index)
+                    .cloned()
+                    .unwrap_or(0),
+            })
+            .collect();
+        let start = ligand.atoms.start;
+        let bonds = ligand
+            .bonds
+            .iter()
+            .map(|bond| (bond.atom1 - start + 1, bond.atom2 - start + 1, bond.order))
+            .collect();
+
+        let chain = mmtf
+            .chain_name_list
+            .as_ref()
+            .map_or(&mmtf.chain_id_list[ligand.chain], |names| {
+                &names[ligand.chain]
+            });
+        let mut name = format!(
+            "{}_{}_{}",
+            chain, ligand.name, mmtf.group_id_list[ligand.group]
+        );
+        if let Some(ref id) = mmtf.structure_id {
+            name = format!("{}_{}", id, name);
+        }
+        Molecule {
+            name,
+            group_name: ligand.name.clone(),
+            atoms,
+            bonds,
+        }
+    }
+
+    /// Highest order of the bonds of the atom with the given 1-based number
+    fn max_bond_order(&self, atom: usize) -> i32 {
+        self.bonds
+            .iter()
+            .filter(|&&(a, b, _)| a == atom || b == atom)
+            .map(|&(_, _, order)| order.unwrap_or(1))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Element symbol of an SDF atom, `*` if unknown
+fn sdf_symbol(atom: &Atom) -> &'static str {
+    atom.element.map_or("*", |element| element.symbol())
+}
+
+/// SDF bond type, `8` (any) for unknown orders and quadruple bonds,
+/// which would otherwise be read as aromatic
+fn sdf_bond_type(order: Option<i32>) -> i32 {
+    order.filter(|order| (1..=3).contains(order)).unwrap_or(8)
+}
+
+/// SDF V2000 charge code of the atom block
+fn sdf_charge_code(charge: i32) -> i32 {
+    match charge {
+        -3..=-1 | 1..=3 => 4 - charge,
+        _ => 0,
+    }
+}
+
+/// SYBYL atom type, with the hybridization guessed from the bond orders
+fn mol2_type(molecule: &Molecule, number: usize) -> String {
+    let atom = &molecule.atoms[number - 1];
+    let element = match atom.element {
+        Some(element) => element,
+        None => return "Du".to_string(),
+    };
+    let order = molecule.max_bond_order(number);
+    let hybridization = match element {
+        Element::C | Element::N if order == 3 => "1",
+        Element::C | Element::N | Element::O | Element::S if order == 2 => "2",
+        Element::C | Element::N | Element::O | Element::S | Element::P => "3",
+        _ => return element.symbol().to_string(),
+    };
+    format!("{}.{}", element.symbol(), hybridization)
+}
+
+/// MOL2 bond type, `un` for unknown orders and quadruple bonds
+fn mol2_bond_type(order: Option<i32>) -> String {
+    match order {
+        Some(order @ 1..=3) => order.to_string(),
+        _ => "un".to_string(),
+    }
+}
+
+fn write_sdf_v2000<W: Write>(writer: &mut W, molecule: &Molecule) -> io::Result<()> {
+    if molecule.atoms.len() > 999 || molecule.bonds.len() > 999 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} exceeds 999 atoms or bonds of SDF V2000", molecule.name),
+        ));
+    }
+    writeln!(
+        writer,
+        "{:>3}{:>3}  0  0  0  0  0  0  0  0999 V2000",
+        molecule.atoms.len(),
+        molecule.bonds.len()
+    )?;
+    for atom in &molecule.atoms {
+        writeln!(
+            writer,
+            "{:>10.4}{:>10.4}{:>10.4} {:<3} 0{:>3}  0  0  0  0  0  0  0  0  0  0",
+            atom.position[0],
+            atom.position[1],
+            atom.position[2],
+            sdf_symbol(atom),
+            sdf_charge_code(atom.charge)
+        )?;
+    }
+    for &(a, b, order) in &molecule.bonds {
+        writeln!(
+            writer,
+            "{:>3}{:>3}{:>3}  0  0  0  0",
+            a,
+            b,
+            sdf_bond_type(order)
+        )?;
+    }
+    let charged: Vec<(usize, i32)> = molecule
+        .atoms
+        .iter()
+        .enumerate()
+        .filter(|&(_, atom)| atom.charge != 0)
+        .map(|(index, atom)| (index + 1, atom.charge))
+        .collect();
+    for chunk in charged.chunks(8) {
+        write!(writer, "M  CHG{:>3}", chunk.len())?;
+        for &(number, charge) in chunk {
+            write!(writer, "{:>4}{:>4}", number, charge)?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+fn write_sdf_v3000<W: Write>(writer: &mut W, molecule: &Molecule) -> io::Result<()> {
+    writeln!(writer, "  0  0  0     0  0            999 V3000")?;
+    writeln!(writer, "M  V30 BEGIN CTAB")?;
+    writeln!(
+        writer,
+        "M  V30 COUNTS {} {} 0 0 0",
+        molecule.atoms.len(),
+        molecule.bonds.len()
+    )?;
+    writeln!(writer, "M  V30 BEGIN ATOM")?;
+    for (index, atom) in molecule.atoms.iter().enumerate() {
+        write!(
+            writer,
+            "M  V30 {} {} {:.4} {:.4} {:.4} 0",
+            index + 1,
+            sdf_symbol(atom),
+            atom.position[0],
+            atom.position[1],
+            atom.position[2]
+        )?;
+        if atom.charge != 0 {
+            write!(writer, " CHG={}", atom.charge)?;
+        }
+        writeln!(writer)?;
+    }
+    writeln!(writer, "M  V30 END ATOM")?;
+    writeln!(writer, "M  V30 BEGIN BOND")?;
+    for (index, &(a, b, order)) in molecule.bonds.iter().enumerate() {
+        writeln!(
+            writer,
+            "M  V30 {} {} {} {}",
+            index + 1,
+            sdf_bond_type(order),
+            a,
+            b
+        )?;
+    }
+    writeln!(writer, "M  V30 END BOND")?;
+    writeln!(writer, "M  V30 END CTAB")
+}
+
+impl Mmtf {
+    /// Write the ligands as SDF records, one record per ligand instance.
+    ///
+    /// Records are named after the structure id, the chain name, the group
+    /// name and the group id. Only bonds within each ligand are written,
+    /// with the orders of [`GroupType.bond_order_list`](../mmtf/struct.GroupType.html#structfield.bond_order_list);
+    /// missing orders are written as bond type `8` (any). Formal charges are
+    /// taken from [`GroupType.formal_charge_list`](../mmtf/struct.GroupType.html#structfield.formal_charge_list).
+    ///
+    /// Fails with [`InvalidInput`](https://doc.rust-lang.org/std/io/enum.ErrorKind.html)
+    /// for V2000 records of more than 999 atoms or bonds.
+    pub fn write_sdf<W: Write>(
+        &self,
+        writer: &mut W,
+        ligands: &[Ligand],
+        version: SdfVersion,
+    ) -> io::Result<()> {
+        for ligand in ligands {
+            let molecule = Molecule::new(self, ligand);
+            writeln!(writer, "{}", molecule.name)?;
+            writeln!(writer, "  {:<8}{:10}3D", PROGRAM, "")?;
+            writeln!(writer)?;
+            match version {
+                SdfVersion::V2000 => write_sdf_v2000(writer, &molecule)?,
+                SdfVersion::V3000 => write_sdf_v3000(writer, &molecule)?,
+            }
+            writeln!(writer, "M  END")?;
+            writeln!(writer, "$$$$")?;
+        }
+        Ok(())
+    }
+
+    /// Ligands as SDF records, see [`write_sdf`](#method.write_sdf)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    /// use mmtf::molfile::SdfVersion;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// let sdf = mmtf.to_sdf(&mmtf.ligands(), SdfVersion::V2000).unwrap();
+    ///
+    /// assert!(sdf.starts_with("173D_C_PXZ_"));
+    /// assert_eq!(2, sdf.matches("$$$$").count());
+    /// ```
+    pub fn to_sdf(&self, ligands: &[Ligand], version: SdfVersion) -> io::Result<String> {
+        let mut buffer = Vec::new();
+        self.write_sdf(&mut buffer, ligands, version)?;
+        Ok(String::from_utf8(buffer).expect("SDF records are valid UTF-8"))
+    }
+
+    /// Write the ligands as Tripos MOL2 records, one record per ligand instance.
+    ///
+    /// Records are named like [SDF records](#method.write_sdf). SYBYL atom
+    /// types of carbon, nitrogen, oxygen, sulfur and phosphorus are derived
+    /// from the highest order of their bonds, other elements are typed by
+    /// their symbol and unknown elements as `Du`. Formal charges are written
+    /// as `USER_CHARGES`.
+    pub fn write_mol2<W: Write>(&self, writer: &mut W, ligands: &[Ligand]) -> io::Result<()> {
+        for ligand in ligands {
+            let molecule = Molecule::new(self, ligand);
+            writeln!(writer, "@<TRIPOS>MOLECULE")?;
+            writeln!(writer, "{}", molecule.name)?;
+            writeln!(
+                writer,
+                "{} {} 1 0 0",
+                molecule.atoms.len(),
+                molecule.bonds.len()
+            )?;
+            writeln!(writer, "SMALL")?;
+            writeln!(writer, "USER_CHARGES")?;
+            writeln!(writer)?;
+
+            writeln!(writer, "@<TRIPOS>ATOM")?;
+            for (index, atom) in molecule.atoms.iter().enumerate() {
+                writeln!(
+                    writer,
+                    "{:>7} {:<5} {:>10.4} {:>10.4} {:>10.4} {:<5} {:>4} {:<8} {:>7.4}",
+                    index + 1,
+                    atom.name,
+                    atom.position[0],
+                    atom.position[1],
+                    atom.position[2],
+                    mol2_type(&molecule, index + 1),
+                    1,
+                    molecule.group_name,
+                    atom.charge as f32
+                )?;
+            }
+            writeln!(writer, "@<TRIPOS>BOND")?;
+            for (index, &(a, b, order)) in molecule.bonds.iter().enumerate() {
+                writeln!(
+                    writer,
+                    "{:>6} {:>5} {:>5} {}",
+                    index + 1,
+                    a,
+                    b,
+                    mol2_bond_type(order)
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Ligands as Tripos MOL2 records, see [`write_mol2`](#method.write_mol2)
+    pub fn to_mol2(&self, ligands: &[Ligand]) -> String {
+        let mut buffer = Vec::new();
+        self.write_mol2(&mut buffer, ligands)
+            .expect("writing to a Vec never fails");
+        String::from_utf8(buffer).expect("MOL2 records are valid UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    #[test]
+    fn it_write_sdf_v2000() {
+        let mmtf = load_173d();
+        let ligands = mmtf.ligands();
+        let sdf = mmtf.to_sdf(&ligands[..1], SdfVersion::V2000).unwrap();
+        let lines: Vec<&str> = sdf.lines().collect();
+
+        let num_atoms = ligands[0].atoms.len();
+        assert_eq!("  mmtf-rs           3D", lines[1]);
+        assert_eq!(
+            format!("{:>3} 13  0  0  0  0  0  0  0  0999 V2000", num_atoms),
+            lines[3]
+        );
+        assert_eq!(69, lines[4].len());
+        assert_eq!(&lines[4][31..34], "C  ");
+        // header, atoms, bonds, end and delimiter
+        assert_eq!(4 + num_atoms + 13 + 2, lines.len());
+        assert_eq!(Some(&"$$$$"), lines.last());
+        // PXZ contains a carbonyl double bond
+        assert!(lines[4 + num_atoms..4 + num_atoms + 13]
+            .iter()
+            .any(|line| &line[6..9] == "  2"));
+    }
+
+    #[test]
+    fn it_write_sdf_v3000() {
+        let mmtf = load_173d();
+        let ligands = mmtf.ligands();
+        let sdf = mmtf.to_sdf(&ligands, SdfVersion::V3000).unwrap();
+
+        assert_eq!(2, sdf.matches("M  V30 BEGIN CTAB").count());
+        assert!(sdf.contains(&format!(
+            "M  V30 COUNTS {} 13 0 0 0",
+            ligands[1].atoms.len()
+        )));
+        let bonds: Vec<&str> = sdf
+            .lines()
+            .skip_while(|line| *line != "M  V30 BEGIN BOND")
+            .skip(1)
+            .take(13)
+            .collect();
+        assert!(bonds.iter().all(|line| line.split(' ').count() == 7));
+        assert!(bonds.iter().any(|line| line.split(' ').nth(4) == Some("2")));
+    }
+
+    #[test]
+    fn it_write_charges() {
+        let mut mmtf = load_173d();
+        let group_type = mmtf.group_type_list[21] as usize;
+        mmtf.group_list[group_type].formal_charge_list[0] = -1;
+        let ligands = mmtf.ligands();
+
+        let v2000 = mmtf.to_sdf(&ligands[..1], SdfVersion::V2000).unwrap();
+        assert!(v2000.contains("M  CHG  1   1  -1\n"));
+        assert!(v2000.lines().nth(4).unwrap().contains(" 0  5  0"));
+        let v3000 = mmtf.to_sdf(&ligands[..1], SdfVersion::V3000).unwrap();
+        assert!(v3000.contains(" CHG=-1\n"));
+        let mol2 = mmtf.to_mol2(&ligands[..1]);
+        assert!(mol2.contains("-1.0000\n"));
+    }
+
+    #[test]
+    fn it_write_mol2() {
+        let mmtf = load_173d();
+        let ligands = mmtf.ligands();
+        let mol2 = mmtf.to_mol2(&ligands);
+
+        assert_eq!(2, mol2.matches("@<TRIPOS>MOLECULE").count());
+        let lines: Vec<&str> = mol2.lines().collect();
+        assert!(lines[1].starts_with("173D_C_PXZ_"));
+        assert_eq!(format!("{} 13 1 0 0", ligands[0].atoms.len()), lines[2]);
+        let atom: Vec<&str> = lines[7].split_whitespace().collect();
+        assert_eq!(9, atom.len());
+        assert_eq!("1", atom[0]);
+        assert_eq!("PXZ", atom[7]);
+        assert!(mol2.contains(" C.2 "));
+        assert!(mol2.contains(" O.2 "));
+    }
+
+    #[test]
+    fn it_derive_sybyl_types() {
+        let atom = |element| Atom {
+            name: String::new(),
+            element,
+            position: [0.0; 3],
+            charge: 0,
+        };
+        let molecule = Molecule {
+            name: String::new(),
+            group_name: String::new(),
+            atoms: vec![
+                atom(Some(Element::C)),
+                atom(Some(Element::N)),
+                atom(Some(Element::Fe)),
+                atom(None),
+            ],
+            bonds: vec![(1, 2, Some(3)), (1, 3, None)],
+        };
+        assert_eq!("C.1", mol2_type(&molecule, 1));
+        assert_eq!("N.1", mol2_type(&molecule, 2));
+        assert_eq!("Fe", mol2_type(&molecule, 3));
+        assert_eq!("Du", mol2_type(&molecule, 4));
+        assert_eq!("8", sdf_bond_type(None).to_string());
+        assert_eq!("un", mol2_bond_type(Some(4)));
+    }
+}
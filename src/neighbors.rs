@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+
+use mmtf::Mmtf;
+
+/// Lattice of a unit cell, used for periodic boundary conditions
+#[derive(Debug, Clone, PartialEq)]
+struct Lattice {
+    /// Cell vectors `a`, `b` and `c`, with `a` along the x-axis and `b` in the xy-plane
+    vectors: [[f32; 3]; 3],
+}
+
+impl Lattice {
+    /// Lattice of the unit cell given as `[a, b, c, alpha, beta, gamma]`,
+    /// lengths in **Å** and angles in degrees
+    fn new(unit_cell: &[f64]) -> Option<Self> {
+        if unit_cell.len() < 6 || unit_cell[..3].iter().any(|length| *length <= 0.0) {
+            return None;
+        }
+        let (a, b, c) = (unit_cell[0], unit_cell[1], unit_cell[2]);
+        let (alpha, beta, gamma) = (
+            unit_cell[3].to_radians(),
+            unit_cell[4].to_radians(),
+            unit_cell[5].to_radians(),
+        );
+        let cx = c * beta.cos();
+        let cy = c * (alpha.cos() - beta.cos() * gamma.cos()) / gamma.sin();
+        let cz = (c * c - cx * cx - cy * cy).sqrt();
+        if !cz.is_finite() || cz <= 0.0 {
+            return None;
+        }
+        Some(Lattice {
+            vectors: [
+                [a as f32, 0.0, 0.0],
+                [(b * gamma.cos()) as f32, (b * gamma.sin()) as f32, 0.0],
+                [cx as f32, cy as f32, cz as f32],
+            ],
+        })
+    }
+
+    /// Translate a position into the unit cell
+    fn wrap(&self, position: [f32; 3]) -> [f32; 3] {
+        let [a, b, c] = self.vectors;
+        let fc = position[2] / c[2];
+        let fb = (position[1] - c[1] * fc) / b[1];
+        let fa = (position[0] - b[0] * fb - c[0] * fc) / a[0];
+        self.translate(position, -fa.floor(), -fb.floor(), -fc.floor())
+    }
+
+    fn translate(&self, position: [f32; 3], fa: f32, fb: f32, fc: f32) -> [f32; 3] {
+        let [a, b, c] = self.vectors;
+        [
+            position[0] + fa * a[0] + fb * b[0] + fc * c[0],
+            position[1] + fa * a[1] + fb * b[1] + fc * c[1],
+            position[2] + fa * a[2] + fb * b[2] + fc * c[2],
+        ]
+    }
+}
+
+fn distance(p: [f32; 3], q: [f32; 3]) -> f32 {
+    ((p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2)).sqrt()
+}
+
+/// Spatial index of atom positions for fast distance queries
+///
+/// Atoms are binned into a uniform grid of cubic cells, so that a query only
+/// compares the atoms of nearby cells. The cell size should be in the order
+/// of the typical query radius.
+///
+/// With [periodic boundaries](#method.with_unit_cell) distances are measured
+/// to the closest periodic image of each atom. Periodic images are only
+/// searched in the directly adjacent unit cells, so query radii should be
+/// smaller than the cell dimensions.
+///
+/// # Examples
+///
+/// ```
+/// # use std::path::Path;
+/// # use std::env;
+/// use std::fs::File;
+/// use mmtf::Mmtf;
+/// use mmtf::neighbors::NeighborSearch;
+///
+/// # let file_path = Path::new(&env::current_dir().unwrap())
+/// #                                    .join("tests")
+/// #                                    .join("data")
+/// #                                    .join("173D.mmtf");
+/// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+/// let search = NeighborSearch::new(&mmtf, 4.0);
+///
+/// let close = search.within(mmtf.position(0), 1.7);
+/// assert_eq!(vec![0, 1], close.iter().map(|&(atom, _)| atom).collect::<Vec<_>>());
+///
+/// let nearest = search.nearest(mmtf.position(0), 2);
+/// assert_eq!(1, nearest[1].0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct NeighborSearch {
+    atoms: Vec<usize>,
+    positions: Vec<[f32; 3]>,
+    cell_size: f32,
+    cells: HashMap<(i32, i32, i32), Vec<usize>>,
+    lattice: Option<Lattice>,
+}
+
+impl NeighborSearch {
+    /// Index all atoms of the structure
+    pub fn new(mmtf: &Mmtf, cell_size: f32) -> Self {
+        Self::with_atoms(mmtf, 0..mmtf.x_coord_list.len(), cell_size)
+    }
+
+    /// Index the given atoms of the structure
+    pub fn with_atoms<I: IntoIterator<Item = usize>>(
+        mmtf: &Mmtf,
+        atoms: I,
+        cell_size: f32,
+    ) -> Self {
+        let atoms: Vec<usize> = atoms.into_iter().collect();
+        let positions = atoms.iter().map(|atom| mmtf.position(*atom)).collect();
+        let mut search = NeighborSearch {
+            atoms,
+            positions,
+            cell_size: cell_size.max(0.1),
+            cells: HashMap::new(),
+            lattice: None,
+        };
+        search.bin();
+        search
+    }
+
+    /// Index the atoms of the given model
+    pub fn model(mmtf: &Mmtf, model: usize, cell_size: f32) -> Self {
+        Self::with_atoms(mmtf, mmtf.hierarchy().model_atoms(model), cell_size)
+    }
+
+    /// Apply periodic boundary conditions of the unit cell given as
+    /// `[a, b, c, alpha, beta, gamma]`, see [`Mmtf.unit_cell`](../mmtf/struct.Mmtf.html#structfield.unit_cell).
+    ///
+    /// An invalid unit cell leaves the search non-periodic.
+    pub fn with_unit_cell(mut self, unit_cell: &[f64]) -> Self {
+        self.lattice = Lattice::new(unit_cell);
+        if let Some(ref lattice) = self.lattice {
+            for position in self.positions.iter_mut() {
+                *position = lattice.wrap(*position);
+            }
+        }
+        self.bin();
+        self
+    }
+
+    /// Number of indexed atoms
+    pub fn len(&self) -> usize {
+        self.atoms.len()
+    }
+
+    /// Whether no atoms are indexed
+    pub fn is_empty(&self) -> bool {
+        self.atoms.is_empty()
+    }
+
+    fn cell(&self, position: [f32; 3]) -> (i32, i32, i32) {
+        (
+            (position[0] / self.cell_size).floor() as i32,
+            (position[1] / self.cell_size).floor() as i32,
+            (position[2] / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn bin(&mut self) {
+        let mut cells: HashMap<(i32, i32, i32), Vec<usize>> = HashMap::new();
+        for (index, position) in self.positions.iter().enumerate() {
+            cells.entry(self.cell(*position)).or_default().push(index);
+        }
+        self.cells = cells;
+    }
+
+    /// Indexed atoms within `radius` of a position, without periodic images
+    fn within_image(&self, position: [f32; 3], radius: f32, found: &mut HashMap<usize, f32>) {
+        let (x, y, z) = self.cell(position);
+        let reach = (radius / self.cell_size).ceil();
+        let mut check = |cell: &Vec<usize>| {
+            for index in cell {
+                let d = distance(position, self.positions[*index]);
+                if d <= radius {
+                    let entry = found.entry(*index).or_insert(d);
+                    *entry = entry.min(d);
+                }
+            }
+        };
+
+        // for large radii visiting the occupied cells is cheaper than the covered ones,
+        // computed in floating point as the cell count of huge radii overflows integers
+        let covered = (2.0 * f64::from(reach) + 1.0).powi(3);
+        if covered.is_nan() || covered > self.cells.len() as f64 {
+            self.cells.values().for_each(check);
+            return;
+        }
+        let reach = reach as i32;
+        for dx in -reach..=reach {
+            for dy in -reach..=reach {
+                for dz in -reach..=reach {
+                    if let Some(cell) = self.cells.get(&(x + dx, y + dy, z + dz)) {
+                        check(cell);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Atoms within `radius` of a position with their distance, by ascending atom index
+    pub fn within(&self, position: [f32; 3], radius: f32) -> Vec<(usize, f32)> {
+        let mut found = HashMap::new();
+        match self.lattice {
+            None => self.within_image(position, radius, &mut found),
+            Some(ref lattice) => {
+                let position = lattice.wrap(position);
+                for fa in -1..2 {
+                    for fb in -1..2 {
+                        for fc in -1..2 {
+                            let image =
+                                lattice.translate(position, fa as f32, fb as f32, fc as f32);
+                            self.within_image(image, radius, &mut found);
+                        }
+                    }
+                }
+            }
+        }
+        let mut result: Vec<(usize, f32)> = found
+            .into_iter()
+            .map(|(index, d)| (self.atoms[index], d))
+            .collect();
+        result.sort_by_key(|&(atom, _)| atom);
+        result
+    }
+
+    /// The `k` atoms closest to a position with their distance, by ascending distance
+    pub fn nearest(&self, position: [f32; 3], k: usize) -> Vec<(usize, f32)> {
+        if self.is_empty() || k == 0 {
+            return Vec::new();
+        }
+        // expand the radius until enough atoms are found or all atoms are covered
+        let mut extent = 0.0_f32;
+        for p in &self.positions {
+            extent = extent.max(distance(position, *p));
+        }
+        let mut radius = self.cell_size;
+        loop {
+            let mut found = self.within(position, radius);
+            if found.len() >= k || radius >= extent {
+                found.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+                found.truncate(k);
+                return found;
+            }
+            radius = (radius * 2.0).min(extent);
+        }
+    }
+
+    /// All pairs of indexed atoms within `cutoff` of each other with their
+    /// distance, the lower atom index first and in ascending order
+    pub fn pairs_within(&self, cutoff: f32) -> Vec<(usize, usize, f32)> {
+        let mut pairs = Vec::new();
+        for (index, atom) in self.atoms.iter().enumerate() {
+            for (other, d) in self.within(self.positions[index], cutoff) {
+                if other > *atom {
+                    pairs.push((*atom, other, d));
+                }
+            }
+        }
+        pairs.sort_by_key(|&(a, b, _)| (a, b));
+        pairs
+    }
+}
+
+impl Mmtf {
+    /// Position of the given atom
+    pub fn position(&self, atom: usize) -> [f32; 3] {
+        [
+            self.x_coord_list[atom],
+            self.y_coord_list[atom],
+            self.z_coord_list[atom],
+        ]
+    }
+
+    /// [`NeighborSearch`](../neighbors/struct.NeighborSearch.html) over all atoms of the structure
+    pub fn neighbor_search(&self, cell_size: f32) -> NeighborSearch {
+        NeighborSearch::new(self, cell_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    fn brute_force(mmtf: &Mmtf, position: [f32; 3], radius: f32) -> Vec<usize> {
+        (0..mmtf.x_coord_list.len())
+            .filter(|atom| distance(position, mmtf.position(*atom)) <= radius)
+            .collect()
+    }
+
+    #[test]
+    fn it_find_atoms_within_radius() {
+        let mmtf = load_173d();
+        let search = mmtf.neighbor_search(3.0);
+        for atom in (0..512).step_by(37) {
+            for radius in [1.0, 4.5, 9.0].iter() {
+                let found: Vec<usize> = search
+                    .within(mmtf.position(atom), *radius)
+                    .iter()
+                    .map(|&(atom, _)| atom)
+                    .collect();
+                assert_eq!(brute_force(&mmtf, mmtf.position(atom), *radius), found);
+            }
+        }
+
+        // huge radii cover every atom instead of overflowing the cell count
+        assert_eq!(512, search.within(mmtf.position(0), 1e6).len());
+        assert_eq!(512, search.within(mmtf.position(0), f32::INFINITY).len());
+    }
+
+    #[test]
+    fn it_find_nearest_atoms() {
+        let mmtf = load_173d();
+        let search = NeighborSearch::new(&mmtf, 2.0);
+        let position = mmtf.position(100);
+        let nearest = search.nearest(position, 5);
+
+        let mut expected: Vec<(usize, f32)> = (0..512)
+            .map(|atom| (atom, distance(position, mmtf.position(atom))))
+            .collect();
+        expected.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        expected.truncate(5);
+
+        assert_eq!(expected, nearest);
+        assert_eq!(100, nearest[0].0);
+        assert_eq!(512, search.nearest([1000.0, 0.0, 0.0], 600).len());
+        assert!(search.nearest([f32::NAN, 0.0, 0.0], 5).len() <= 5);
+    }
+
+    #[test]
+    fn it_find_pairs_within_cutoff() {
+        let mmtf = load_173d();
+        let water = mmtf.select_atoms("water").unwrap();
+        let search = NeighborSearch::with_atoms(&mmtf, water.iter(), 3.5);
+        let pairs = search.pairs_within(3.5);
+
+        let atoms = water.indices();
+        let mut expected = Vec::new();
+        for (i, a) in atoms.iter().enumerate() {
+            for b in &atoms[i + 1..] {
+                if distance(mmtf.position(*a), mmtf.position(*b)) <= 3.5 {
+                    expected.push((*a, *b));
+                }
+            }
+        }
+        assert!(!expected.is_empty());
+        assert_eq!(
+            expected,
+            pairs.iter().map(|&(a, b, _)| (a, b)).collect::<Vec<_>>()
+        );
+        assert_eq!(512, NeighborSearch::model(&mmtf, 0, 4.0).len());
+    }
+
+    #[test]
+    fn it_apply_periodic_boundaries() {
+        let mut mmtf = load_173d();
+        mmtf.x_coord_list[..3].copy_from_slice(&[0.5, 19.5, 10.0]);
+        mmtf.y_coord_list[..3].copy_from_slice(&[5.0, 5.0, 5.0]);
+        mmtf.z_coord_list[..3].copy_from_slice(&[5.0, 5.0, 25.0]);
+        let cell = [20.0, 20.0, 20.0, 90.0, 90.0, 90.0];
+        let search = NeighborSearch::with_atoms(&mmtf, 0..3, 4.0).with_unit_cell(&cell);
+
+        let found = search.within(mmtf.position(0), 1.5);
+        assert_eq!(
+            vec![0, 1],
+            found.iter().map(|&(atom, _)| atom).collect::<Vec<_>>()
+        );
+        assert!((found[1].1 - 1.0).abs() < 1e-4);
+
+        // atom 2 is a periodic image away from the unit cell
+        let found = search.within([10.0, 5.0, 5.5], 0.6);
+        assert_eq!(
+            vec![2],
+            found.iter().map(|&(atom, _)| atom).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(0, 1)],
+            search
+                .pairs_within(1.5)
+                .iter()
+                .map(|&(a, b, _)| (a, b))
+                .collect::<Vec<_>>()
+        );
+
+        // a monoclinic cell is sheared along the c-axis
+        let lattice = Lattice::new(&[10.0, 10.0, 10.0, 90.0, 120.0, 90.0]).unwrap();
+        assert!((lattice.vectors[2][0] + 5.0).abs() < 1e-4);
+        assert!(Lattice::new(&[0.0, 10.0, 10.0, 90.0, 90.0, 90.0]).is_none());
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use chain::Chain;
+use hierarchy::Hierarchy;
+use mmtf::Mmtf;
+use selection::AtomSet;
+
+/// A residue position of a chain together with all of its variant groups
+///
+/// Groups of a chain with the same sequence index are variants of one residue
+/// (micro-heterogeneity). Groups without sequence index, such as ligands and
+/// waters, are identified by their group id and insertion code instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Residue {
+    /// Index of the chain the residue belongs to
+    pub chain: usize,
+
+    /// Index into the entity sequence, `-1` for groups without sequence
+    pub sequence_index: i32,
+
+    /// Indices of the variant groups, in file order
+    pub groups: Vec<usize>,
+}
+
+impl Residue {
+    /// Whether more than one group is given for this residue
+    pub fn is_heterogeneous(&self) -> bool {
+        self.groups.len() > 1
+    }
+
+    /// Index of the group chosen as canonical variant of this residue
+    pub fn canonical_group(&self, mmtf: &Mmtf, variant: Variant) -> usize {
+        if !self.is_heterogeneous() {
+            return self.groups[0];
+        }
+        self.canonical_group_in(mmtf, &mmtf.hierarchy(), variant)
+    }
+
+    fn canonical_group_in(&self, mmtf: &Mmtf, hierarchy: &Hierarchy, variant: Variant) -> usize {
+        if !self.is_heterogeneous() {
+            return self.groups[0];
+        }
+        let score = |group: usize| -> f32 {
+            let atoms = hierarchy.group_atoms[group].clone();
+            match variant {
+                Variant::First => 0.0,
+                Variant::MostAtoms => atoms.len() as f32,
+                Variant::HighestOccupancy => match mmtf.occupancy_list {
+                    Some(ref occupancy) if !atoms.is_empty() => {
+                        atoms.clone().map(|atom| occupancy[atom]).sum::<f32>() / atoms.len() as f32
+                    }
+                    _ => 0.0,
+                },
+            }
+        };
+        // the earliest group wins ties
+        self.groups
+            .iter()
+            .fold((self.groups[0], score(self.groups[0])), |best, group| {
+                let value = score(*group);
+                if value > best.1 {
+                    (*group, value)
+                } else {
+                    best
+                }
+            })
+            .0
+    }
+}
+
+/// Rule to pick the canonical variant of a micro-heterogeneous residue
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// The first group given in the file
+    #[default]
+    First,
+
+    /// The group with the most atoms
+    MostAtoms,
+
+    /// The group with the highest mean atom occupancy
+    HighestOccupancy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ResidueKey {
+    Sequence(i32),
+    Id(i32, char),
+}
+
+impl Mmtf {
+    /// Residues of the given chain, in order of their first group
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// let residues = mmtf.chain_residues(0);
+    ///
+    /// assert_eq!(8, residues.len());
+    /// assert!(residues.iter().all(|residue| !residue.is_heterogeneous()));
+    /// ```
+    pub fn chain_residues(&self, chain: usize) -> Vec<Residue> {
+        self.group_residues(chain, Chain::new(self, chain).groups())
+    }
+
+    /// Residues formed by the given groups of the chain
+    pub(crate) fn group_residues(&self, chain: usize, groups: Range<usize>) -> Vec<Residue> {
+        let mut residues: Vec<Residue> = Vec::new();
+        let mut positions: HashMap<ResidueKey, usize> = HashMap::new();
+
+        for group in groups {
+            let sequence_index = self
+                .sequence_index_list
+                .as_ref()
+                .map_or(-1, |list| list[group]);
+            let key = if sequence_index >= 0 {
+                ResidueKey::Sequence(sequence_index)
+            } else {
+                let ins_code = self
+                    .ins_code_list
+                    .as_ref()
+                    .map_or('\0', |codes| codes[group]);
+                ResidueKey::Id(self.group_id_list[group], ins_code)
+            };
+
+            match positions.get(&key) {
+                Some(&position) => residues[position].groups.push(group),
+                None => {
+                    positions.insert(key, residues.len());
+                    residues.push(Residue {
+                        chain,
+                        sequence_index,
+                        groups: vec![group],
+                    });
+                }
+            }
+        }
+        residues
+    }
+
+    /// Residues of all chains
+    pub fn residues(&self) -> Vec<Residue> {
+        self.residues_in(&self.hierarchy())
+    }
+
+    fn residues_in(&self, hierarchy: &Hierarchy) -> Vec<Residue> {
+        (0..hierarchy.num_chains())
+            .flat_map(|chain| self.group_residues(chain, hierarchy.chain_groups[chain].clone()))
+            .collect()
+    }
+
+    /// Residues with more than one variant group, for all chains
+    pub fn heterogeneous_residues(&self) -> Vec<Residue> {
+        self.residues()
+            .into_iter()
+            .filter(Residue::is_heterogeneous)
+            .collect()
+    }
+
+    /// One group per residue, chosen by the given rule, in ascending order
+    pub fn canonical_groups(&self, variant: Variant) -> Vec<usize> {
+        self.canonical_groups_in(&self.hierarchy(), variant)
+    }
+
+    fn canonical_groups_in(&self, hierarchy: &Hierarchy, variant: Variant) -> Vec<usize> {
+        let mut groups: Vec<usize> = self
+            .residues_in(hierarchy)
+            .iter()
+            .map(|residue| residue.canonical_group_in(self, hierarchy, variant))
+            .collect();
+        groups.sort();
+        groups
+    }
+
+    /// Structure with the canonical variant of every residue only
+    pub fn keep_canonical_variants(&self, variant: Variant) -> Mmtf {
+        let hierarchy = self.hierarchy();
+        let mut atoms = AtomSet::empty(hierarchy.num_atoms());
+        for group in self.canonical_groups_in(&hierarchy, variant) {
+            for atom in hierarchy.group_atoms[group].clone() {
+                atoms.insert(atom);
+            }
+        }
+        self.select(&atoms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    /// 173D where the second and third group of the first chain are two
+    /// variants of the second residue
+    fn load_heterogeneous() -> Mmtf {
+        let mut mmtf = load_173d();
+        if let Some(ref mut sequence_index) = mmtf.sequence_index_list {
+            sequence_index[2] = sequence_index[1];
+        }
+        mmtf
+    }
+
+    #[test]
+    fn it_group_variants_by_sequence_index() {
+        let mmtf = load_heterogeneous();
+        let residues = mmtf.chain_residues(0);
+
+        assert_eq!(7, residues.len());
+        assert_eq!(vec![1, 2], residues[1].groups);
+        assert!(residues[1].is_heterogeneous());
+        assert_eq!(vec![3], residues[2].groups);
+
+        let heterogeneous = mmtf.heterogeneous_residues();
+        assert_eq!(1, heterogeneous.len());
+        assert_eq!(0, heterogeneous[0].chain);
+        assert!(load_173d().heterogeneous_residues().is_empty());
+    }
+
+    #[test]
+    fn it_keep_waters_apart() {
+        let mmtf = load_173d();
+        let waters = mmtf.chain_residues(4);
+        assert_eq!(46, waters.len());
+        assert!(waters.iter().all(|residue| residue.sequence_index == -1));
+        assert_eq!(124, mmtf.residues().len());
+    }
+
+    #[test]
+    fn it_choose_canonical_variant() {
+        let mut mmtf = load_heterogeneous();
+        let hierarchy = mmtf.hierarchy();
+        let residue = mmtf.chain_residues(0)[1].clone();
+        assert_eq!(1, residue.canonical_group(&mmtf, Variant::First));
+
+        let (first, second) = (
+            hierarchy.group_atoms[1].len(),
+            hierarchy.group_atoms[2].len(),
+        );
+        let larger = if second > first { 2 } else { 1 };
+        assert_eq!(larger, residue.canonical_group(&mmtf, Variant::MostAtoms));
+
+        let mut occupancy = vec![1.0; 512];
+        for atom in hierarchy.group_atoms[1].clone() {
+            occupancy[atom] = 0.25;
+        }
+        mmtf.occupancy_list = Some(occupancy);
+        assert_eq!(2, residue.canonical_group(&mmtf, Variant::HighestOccupancy));
+
+        let single = mmtf.keep_canonical_variants(Variant::HighestOccupancy);
+        assert_eq!(123, single.num_groups);
+        assert_eq!(512 - first as i32, single.num_atoms);
+        assert_eq!(7, single.groups_per_chain[0]);
+    }
+}
@@ -0,0 +1,300 @@
+use std::f32::consts::PI;
+
+use element::Element;
+use mmtf::Mmtf;
+use neighbors::NeighborSearch;
+
+/// Theoretical maximum accessible surface area in **Å²** of an amino acid in
+/// a Gly-X-Gly tripeptide (Tien et al., 2013), used for relative accessibility
+pub fn max_asa(group_name: &str) -> Option<f32> {
+    let value = match group_name.to_ascii_uppercase().as_str() {
+        "ALA" => 129.0,
+        "ARG" => 274.0,
+        "ASN" => 195.0,
+        "ASP" => 193.0,
+        "CYS" => 167.0,
+        "GLN" => 225.0,
+        "GLU" => 223.0,
+        "GLY" => 104.0,
+        "HIS" => 224.0,
+        "ILE" => 197.0,
+        "LEU" => 201.0,
+        "LYS" => 236.0,
+        "MET" => 224.0,
+        "PHE" => 240.0,
+        "PRO" => 159.0,
+        "SER" => 155.0,
+        "THR" => 172.0,
+        "TRP" => 285.0,
+        "TYR" => 263.0,
+        "VAL" => 174.0,
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Solvent accessible surface area calculation with the Shrake–Rupley algorithm
+///
+/// Each atom is represented by a sphere of its element's van der Waals radius
+/// enlarged by the probe radius. The accessible area of an atom is the fraction
+/// of points on its sphere that are not buried in the sphere of any other atom
+/// of the same model.
+#[derive(Debug, Clone)]
+pub struct ShrakeRupley {
+    /// Radius of the solvent probe in **Å**
+    pub probe_radius: f32,
+
+    /// Number of test points on each atom sphere
+    pub num_points: usize,
+
+    /// Whether hydrogen atoms take part in the calculation
+    pub include_hydrogens: bool,
+
+    /// Whether water molecules take part in the calculation
+    pub include_waters: bool,
+}
+
+impl Default for ShrakeRupley {
+    fn default() -> Self {
+        ShrakeRupley {
+            probe_radius: 1.4,
+            num_points: 100,
+            include_hydrogens: false,
+            include_waters: false,
+        }
+    }
+}
+
+/// Solvent accessible surface areas in **Å²**
+///
+/// Atoms excluded from the calculation have an area of zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sasa {
+    /// Area of each atom
+    pub atoms: Vec<f32>,
+
+    /// Area of each group, the sum of its atoms
+    pub groups: Vec<f32>,
+
+    /// Area of each chain, the sum of its groups
+    pub chains: Vec<f32>,
+}
+
+impl Sasa {
+    /// Total area of the structure
+    pub fn total(&self) -> f32 {
+        self.atoms.iter().sum()
+    }
+
+    /// Area of each group relative to its [maximum area](fn.max_asa.html),
+    /// `None` for groups other than the standard amino acids
+    pub fn relative(&self, mmtf: &Mmtf) -> Vec<Option<f32>> {
+        mmtf.group_type_list
+            .iter()
+            .zip(&self.groups)
+            .map(|(group_type, area)| {
+                max_asa(&mmtf.group_list[*group_type as usize].group_name).map(|max| area / max)
+            })
+            .collect()
+    }
+}
+
+/// Evenly distributed points on the unit sphere (golden section spiral)
+fn sphere_points(count: usize) -> Vec<[f32; 3]> {
+    let increment = PI * (3.0 - 5.0_f32.sqrt());
+    let offset = 2.0 / count as f32;
+    (0..count)
+        .map(|i| {
+            let y = i as f32 * offset - 1.0 + offset / 2.0;
+            let r = (1.0 - y * y).max(0.0).sqrt();
+            let phi = i as f32 * increment;
+            [phi.cos() * r, y, phi.sin() * r]
+        })
+        .collect()
+}
+
+impl ShrakeRupley {
+    /// Compute the accessible surface areas of the structure
+    pub fn compute(&self, mmtf: &Mmtf) -> Sasa {
+        let hierarchy = mmtf.hierarchy();
+        let num_atoms = hierarchy.num_atoms();
+        let elements = mmtf.atom_elements();
+        let waters: Vec<bool> = mmtf
+            .group_list
+            .iter()
+            .map(|group_type| group_type.is_water())
+            .collect();
+
+        let radii: Vec<Option<f32>> = (0..num_atoms)
+            .map(|atom| {
+                let group_type = mmtf.group_type_list[hierarchy.atom_group[atom]] as usize;
+                match elements[atom] {
+                    Some(Element::H) if !self.include_hydrogens => None,
+                    _ if waters[group_type] && !self.include_waters => None,
+                    Some(element) => Some(element.vdw_radius() + self.probe_radius),
+                    None => Some(Element::C.vdw_radius() + self.probe_radius),
+                }
+            })
+            .collect();
+        let max_radius = radii.iter().filter_map(|r| *r).fold(0.0_f32, f32::max);
+        let points = sphere_points(self.num_points.max(1));
+
+        let mut atoms = vec![0.0; num_atoms];
+        for model in 0..hierarchy.num_models() {
+            let included = hierarchy
+                .model_atoms(model)
+                .filter(|atom| radii[*atom].is_some());
+            let search = NeighborSearch::with_atoms(mmtf, included, 2.0 * max_radius);
+
+            for atom in hierarchy.model_atoms(model) {
+                let radius = match radii[atom] {
+                    Some(radius) => radius,
+                    None => continue,
+                };
+                let center = mmtf.position(atom);
+                let neighbors: Vec<([f32; 3], f32)> = search
+                    .within(center, radius + max_radius)
+                    .into_iter()
+                    .filter(|&(other, distance)| {
+                        other != atom && distance < radius + radii[other].unwrap_or(0.0)
+                    })
+                    .map(|(other, _)| (mmtf.position(other), radii[other].unwrap_or(0.0)))
+                    .collect();
+
+                let accessible = points
+                    .iter()
+                    .filter(|point| {
+                        let p = [
+                            center[0] + radius * point[0],
+                            center[1] + radius * point[1],
+                            center[2] + radius * point[2],
+                        ];
+                        !neighbors.iter().any(|&(q, r)| {
+                            (p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2)
+                                < r * r
+                        })
+                    })
+                    .count();
+                atoms[atom] = 4.0 * PI * radius * radius * accessible as f32 / points.len() as f32;
+            }
+        }
+
+        let groups: Vec<f32> = hierarchy
+            .group_atoms
+            .iter()
+            .map(|range| atoms[range.clone()].iter().sum())
+            .collect();
+        let chains = hierarchy
+            .chain_groups
+            .iter()
+            .map(|range| groups[range.clone()].iter().sum())
+            .collect();
+        Sasa {
+            atoms,
+            groups,
+            chains,
+        }
+    }
+}
+
+impl Mmtf {
+    /// Solvent accessible surface areas with the default [`ShrakeRupley`](../sasa/struct.ShrakeRupley.html) settings
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// let sasa = mmtf.sasa();
+    ///
+    /// assert_eq!(mmtf.num_chains as usize, sasa.chains.len());
+    /// assert!(sasa.total() > 0.0);
+    /// ```
+    pub fn sasa(&self) -> Sasa {
+        ShrakeRupley::default().compute(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use selection::AtomSet;
+    use test_support::load_173d;
+
+    #[test]
+    fn it_distribute_sphere_points() {
+        let points = sphere_points(200);
+        let mut center = [0.0; 3];
+        for point in &points {
+            let norm = (point[0].powi(2) + point[1].powi(2) + point[2].powi(2)).sqrt();
+            assert!((norm - 1.0).abs() < 1e-5);
+            for k in 0..3 {
+                center[k] += point[k] / 200.0;
+            }
+        }
+        assert!(center.iter().all(|c| c.abs() < 0.02));
+    }
+
+    #[test]
+    fn it_compute_isolated_atom() {
+        let mmtf = load_173d();
+        let single = mmtf.select(&AtomSet::from_indices(512, vec![1]));
+        let sasa = single.sasa();
+        let radius = Element::C.vdw_radius() + 1.4;
+        assert!((sasa.atoms[0] - 4.0 * PI * radius * radius).abs() < 1e-3);
+        assert_eq!(sasa.atoms[0], sasa.total());
+    }
+
+    #[test]
+    fn it_sum_areas_per_group_and_chain() {
+        let mmtf = load_173d();
+        let sasa = mmtf.sasa();
+
+        assert_eq!(512, sasa.atoms.len());
+        assert_eq!(124, sasa.groups.len());
+        assert!((sasa.chains.iter().sum::<f32>() - sasa.total()).abs() < 1e-1);
+        // waters are excluded by default
+        assert_eq!(0.0, sasa.chains[4]);
+
+        let isolated: f32 = mmtf
+            .chains()
+            .iter()
+            .take(2)
+            .map(|chain| {
+                let atoms = mmtf.hierarchy().chain_atoms(chain.index());
+                mmtf.select(&AtomSet::from_indices(512, atoms))
+                    .sasa()
+                    .total()
+            })
+            .sum();
+        // the DNA strands bury part of each other's surface
+        assert!(sasa.chains[0] + sasa.chains[1] < isolated);
+
+        let with_waters = ShrakeRupley {
+            include_waters: true,
+            ..Default::default()
+        }
+        .compute(&mmtf);
+        assert!(with_waters.chains[4] > 0.0);
+    }
+
+    #[test]
+    fn it_compute_relative_accessibility() {
+        let mmtf = load_173d();
+        let relative = mmtf.sasa().relative(&mmtf);
+
+        // THR and PRO of the actinomycin peptide are standard amino acids
+        assert!(relative[16].is_some_and(|r| r > 0.0 && r < 1.5));
+        assert!(relative[18].is_some());
+        assert_eq!(None, relative[0]);
+        assert_eq!(Some(129.0), max_asa("ala"));
+    }
+}
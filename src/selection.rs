@@ -0,0 +1,812 @@
+use std::fmt;
+use std::str::FromStr;
+
+use element::Element;
+use hierarchy::Hierarchy;
+use mmtf::{EntityType, GroupType, Mmtf};
+use neighbors::NeighborSearch;
+
+/// Set of atom indices of a structure
+///
+/// An `AtomSet` is a mask with one entry for each atom of the structure
+/// it was created for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtomSet {
+    mask: Vec<bool>,
+}
+
+impl AtomSet {
+    /// Empty set for a structure with `num_atoms` atoms
+    pub fn empty(num_atoms: usize) -> Self {
+        AtomSet {
+            mask: vec![false; num_atoms],
+        }
+    }
+
+    /// Set of all atoms of a structure with `num_atoms` atoms
+    pub fn all(num_atoms: usize) -> Self {
+        AtomSet {
+            mask: vec![true; num_atoms],
+        }
+    }
+
+    /// Set of the given atom indices, out of range indices are ignored
+    pub fn from_indices<I: IntoIterator<Item = usize>>(num_atoms: usize, indices: I) -> Self {
+        let mut set = AtomSet::empty(num_atoms);
+        for index in indices {
+            set.insert(index);
+        }
+        set
+    }
+
+    /// Set of the atoms for which `predicate` returns `true`
+    pub fn from_fn<F: FnMut(usize) -> bool>(num_atoms: usize, predicate: F) -> Self {
+        AtomSet {
+            mask: (0..num_atoms).map(predicate).collect(),
+        }
+    }
+
+    /// Number of atoms of the structure, selected or not
+    pub fn num_atoms(&self) -> usize {
+        self.mask.len()
+    }
+
+    /// Number of selected atoms
+    pub fn len(&self) -> usize {
+        self.mask.iter().filter(|selected| **selected).count()
+    }
+
+    /// `true` when no atom is selected
+    pub fn is_empty(&self) -> bool {
+        !self.mask.iter().any(|selected| *selected)
+    }
+
+    /// `true` when the given atom is selected
+    pub fn contains(&self, atom: usize) -> bool {
+        self.mask.get(atom).cloned().unwrap_or(false)
+    }
+
+    /// Add an atom to the set
+    pub fn insert(&mut self, atom: usize) {
+        if let Some(selected) = self.mask.get_mut(atom) {
+            *selected = true;
+        }
+    }
+
+    /// Remove an atom from the set
+    pub fn remove(&mut self, atom: usize) {
+        if let Some(selected) = self.mask.get_mut(atom) {
+            *selected = false;
+        }
+    }
+
+    /// Iterate over the selected atom indices in ascending order
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
+        self.mask
+            .iter()
+            .enumerate()
+            .filter(|&(_, selected)| *selected)
+            .map(|(index, _)| index)
+    }
+
+    /// Selected atom indices in ascending order
+    pub fn indices(&self) -> Vec<usize> {
+        self.iter().collect()
+    }
+
+    /// The selection mask, one entry for each atom
+    pub fn mask(&self) -> &[bool] {
+        &self.mask
+    }
+
+    /// Atoms selected in either set
+    pub fn union(&self, other: &AtomSet) -> AtomSet {
+        self.combine(other, |a, b| a || b)
+    }
+
+    /// Atoms selected in both sets
+    pub fn intersection(&self, other: &AtomSet) -> AtomSet {
+        self.combine(other, |a, b| a && b)
+    }
+
+    /// Atoms selected in this set but not in `other`
+    pub fn difference(&self, other: &AtomSet) -> AtomSet {
+        self.combine(other, |a, b| a && !b)
+    }
+
+    /// Atoms not selected in this set
+    pub fn complement(&self) -> AtomSet {
+        AtomSet {
+            mask: self.mask.iter().map(|selected| !selected).collect(),
+        }
+    }
+
+    fn combine<F: Fn(bool, bool) -> bool>(&self, other: &AtomSet, f: F) -> AtomSet {
+        AtomSet {
+            mask: (0..self.num_atoms())
+                .map(|atom| f(self.contains(atom), other.contains(atom)))
+                .collect(),
+        }
+    }
+}
+
+/// Errors raised while parsing a selection
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionError {
+    /// The selection ended while more input was expected
+    UnexpectedEnd,
+    /// A token which is not valid at its position
+    UnexpectedToken(String),
+    /// A value which could not be parsed as a number or range
+    InvalidNumber(String),
+    /// An unknown element symbol
+    InvalidElement(String),
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SelectionError::UnexpectedEnd => write!(f, "Unexpected end of selection"),
+            SelectionError::UnexpectedToken(ref token) => write!(f, "Unexpected token `{}`", token),
+            SelectionError::InvalidNumber(ref value) => write!(f, "Invalid number `{}`", value),
+            SelectionError::InvalidElement(ref value) => write!(f, "Invalid element `{}`", value),
+        }
+    }
+}
+
+/// Parsed atom selection
+///
+/// The selection language combines the following terms with `and`, `or`,
+/// `not` and parentheses. Keywords are case insensitive, multiple values can
+/// be separated by spaces or `+`, and quotes allow empty or reserved values.
+///
+/// | Term                    | Selects atoms                                               |
+/// |-------------------------|-------------------------------------------------------------|
+/// | `all`, `none`           | all or no atoms                                             |
+/// | `chain A B`             | of chains by name (or by id, when there are no chain names) |
+/// | `resn HOH`              | of groups by group name                                     |
+/// | `resi 10-50 60`         | of groups by group id (residue number) or range             |
+/// | `name CA`               | by atom name                                                |
+/// | `element Fe`            | by element                                                  |
+/// | `altloc A ''`           | by alternate location label, `''` for none                  |
+/// | `model 1`               | of models by number, starting at 1                          |
+/// | `index 0-9`             | by atom index, starting at 0                                |
+/// | `within 5 of <term>`    | within the distance in **Å** of the term, in the same model |
+/// | `polymer`               | of groups in polymer entities, see below                    |
+/// | `protein`, `nucleic`    | of amino acid or nucleotide groups                          |
+/// | `water`, `ligand`       | of water or ligand groups                                   |
+/// | `hydrogen`              | of hydrogen element                                         |
+/// | `backbone`              | of the protein or nucleic acid backbone                     |
+///
+/// Structures without an `entity_list` have no polymer entities, `polymer`
+/// then selects the amino acid and nucleotide groups.
+///
+/// # Examples
+///
+/// ```
+/// # use std::path::Path;
+/// # use std::env;
+/// use std::fs::File;
+/// use mmtf::Mmtf;
+/// use mmtf::selection::Selection;
+///
+/// # let file_path = Path::new(&env::current_dir().unwrap())
+/// #                                    .join("tests")
+/// #                                    .join("data")
+/// #                                    .join("173D.mmtf");
+/// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+///
+/// let selection = Selection::parse("chain A and resi 1-4 and name P").unwrap();
+/// assert_eq!(3, selection.evaluate(&mmtf).len());
+///
+/// let pocket = mmtf.select_atoms("polymer and within 5 of resn PXZ").unwrap();
+/// assert!(!pocket.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selection {
+    /// All atoms
+    All,
+    /// No atoms
+    None,
+    /// Atoms not in the selection
+    Not(Box<Selection>),
+    /// Atoms in both selections
+    And(Box<Selection>, Box<Selection>),
+    /// Atoms in either selection
+    Or(Box<Selection>, Box<Selection>),
+    /// Chain names
+    Chain(Vec<String>),
+    /// Group names
+    ResName(Vec<String>),
+    /// Inclusive ranges of group ids
+    ResId(Vec<(i32, i32)>),
+    /// Atom names
+    Name(Vec<String>),
+    /// Elements
+    Element(Vec<Element>),
+    /// Alternate location labels, `'\0'` for none
+    AltLoc(Vec<char>),
+    /// Inclusive ranges of model numbers, starting at 1
+    Model(Vec<(i32, i32)>),
+    /// Inclusive ranges of atom indices
+    Index(Vec<(i32, i32)>),
+    /// Atoms within a distance of the selection
+    Within(f32, Box<Selection>),
+    /// Groups of polymer entities, or amino acids and nucleotides if the
+    /// structure has no entities
+    Polymer,
+    /// Amino acid groups
+    Protein,
+    /// Nucleotide groups
+    Nucleic,
+    /// Water groups
+    Water,
+    /// Ligand groups
+    Ligand,
+    /// Hydrogen atoms
+    Hydrogen,
+    /// Protein and nucleic acid backbone atoms
+    Backbone,
+}
+
+const PROTEIN_BACKBONE: [&str; 4] = ["N", "CA", "C", "O"];
+const NUCLEIC_BACKBONE: [&str; 8] = ["P", "OP1", "OP2", "O5'", "C5'", "C4'", "C3'", "O3'"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Token::Word(ref word) => write!(f, "{}", word),
+            Token::Quoted(ref value) => write!(f, "'{}'", value),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, SelectionError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() || c == '+' {
+            chars.next();
+        } else if c == '(' {
+            chars.next();
+            tokens.push(Token::Open);
+        } else if c == ')' {
+            chars.next();
+            tokens.push(Token::Close);
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some(q) if q == c => break,
+                    Some(other) => value.push(other),
+                    None => return Err(SelectionError::UnexpectedEnd),
+                }
+            }
+            tokens.push(Token::Quoted(value));
+        } else {
+            let mut value = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '+' || c == '(' || c == ')' {
+                    break;
+                }
+                value.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(value));
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_operator(word: &str) -> bool {
+    ["and", "or", "not", "of"]
+        .iter()
+        .any(|op| op.eq_ignore_ascii_case(word))
+}
+
+fn parse_range(value: &str) -> Result<(i32, i32), SelectionError> {
+    let invalid = || SelectionError::InvalidNumber(value.to_string());
+    let split = value
+        .char_indices()
+        .skip(1)
+        .find(|&(_, c)| c == '-' || c == ':')
+        .map(|(index, _)| index);
+    match split {
+        Some(index) => {
+            let start = value[..index].parse().map_err(|_| invalid())?;
+            let end = value[index + 1..].parse().map_err(|_| invalid())?;
+            Ok((start, end))
+        }
+        None => {
+            let number = value.parse().map_err(|_| invalid())?;
+            Ok((number, number))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek_word(&self) -> Option<&str> {
+        match self.tokens.get(self.position) {
+            Some(Token::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    /// Error for the token at the current position
+    fn unexpected(&self) -> SelectionError {
+        match self.tokens.get(self.position) {
+            Some(token) => SelectionError::UnexpectedToken(token.to_string()),
+            None => SelectionError::UnexpectedEnd,
+        }
+    }
+
+    fn accept(&mut self, keyword: &str) -> bool {
+        let found = self
+            .peek_word()
+            .is_some_and(|word| word.eq_ignore_ascii_case(keyword));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Selection, SelectionError> {
+        let mut selection = self.and()?;
+        while self.accept("or") {
+            selection = Selection::Or(Box::new(selection), Box::new(self.and()?));
+        }
+        Ok(selection)
+    }
+
+    fn and(&mut self) -> Result<Selection, SelectionError> {
+        let mut selection = self.not()?;
+        while self.accept("and") {
+            selection = Selection::And(Box::new(selection), Box::new(self.not()?));
+        }
+        Ok(selection)
+    }
+
+    fn not(&mut self) -> Result<Selection, SelectionError> {
+        if self.accept("not") {
+            Ok(Selection::Not(Box::new(self.not()?)))
+        } else {
+            self.term()
+        }
+    }
+
+    /// Values following a keyword, up to the next operator or parenthesis
+    fn values(&mut self) -> Result<Vec<String>, SelectionError> {
+        let mut values = Vec::new();
+        loop {
+            match self.tokens.get(self.position) {
+                Some(Token::Word(word)) if !is_operator(word) => values.push(word.clone()),
+                Some(Token::Quoted(value)) => values.push(value.clone()),
+                _ => break,
+            }
+            self.position += 1;
+        }
+        if values.is_empty() {
+            Err(self.unexpected())
+        } else {
+            Ok(values)
+        }
+    }
+
+    fn ranges(&mut self) -> Result<Vec<(i32, i32)>, SelectionError> {
+        self.values()?.iter().map(|v| parse_range(v)).collect()
+    }
+
+    fn term(&mut self) -> Result<Selection, SelectionError> {
+        let keyword = match self.tokens.get(self.position) {
+            Some(&Token::Open) => {
+                self.position += 1;
+                let selection = self.or()?;
+                if self.tokens.get(self.position) != Some(&Token::Close) {
+                    return Err(self.unexpected());
+                }
+                self.position += 1;
+                return Ok(selection);
+            }
+            Some(Token::Word(word)) if !is_operator(word) => word.to_lowercase(),
+            _ => return Err(self.unexpected()),
+        };
+        self.position += 1;
+
+        let selection = match &keyword[..] {
+            "all" => Selection::All,
+            "none" => Selection::None,
+            "polymer" => Selection::Polymer,
+            "protein" => Selection::Protein,
+            "nucleic" => Selection::Nucleic,
+            "water" => Selection::Water,
+            "ligand" => Selection::Ligand,
+            "hydrogen" => Selection::Hydrogen,
+            "backbone" => Selection::Backbone,
+            "chain" => Selection::Chain(self.values()?),
+            "resn" => Selection::ResName(self.values()?),
+            "name" => Selection::Name(self.values()?),
+            "resi" => Selection::ResId(self.ranges()?),
+            "model" => Selection::Model(self.ranges()?),
+            "index" => Selection::Index(self.ranges()?),
+            "element" => Selection::Element(
+                self.values()?
+                    .iter()
+                    .map(|v| {
+                        Element::from_symbol(v)
+                            .ok_or_else(|| SelectionError::InvalidElement(v.clone()))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            "altloc" => Selection::AltLoc(
+                self.values()?
+                    .iter()
+                    .map(|v| v.chars().next().unwrap_or('\0'))
+                    .collect(),
+            ),
+            "within" => {
+                let distance = match self.peek_word() {
+                    Some(word) => word
+                        .parse::<f32>()
+                        .map_err(|_| SelectionError::InvalidNumber(word.to_string()))?,
+                    None => return Err(self.unexpected()),
+                };
+                self.position += 1;
+                if !self.accept("of") {
+                    return Err(self.unexpected());
+                }
+                Selection::Within(distance, Box::new(self.not()?))
+            }
+            _ => return Err(SelectionError::UnexpectedToken(keyword)),
+        };
+        Ok(selection)
+    }
+}
+
+/// Per atom data needed to evaluate selections
+#[derive(Debug)]
+struct Context<'a> {
+    mmtf: &'a Mmtf,
+    hierarchy: Hierarchy,
+    atom_chain: Vec<usize>,
+    atom_model: Vec<usize>,
+    elements: Vec<Option<Element>>,
+    /// Whether each chain belongs to a polymer entity, `None` without entities
+    polymer_chains: Option<Vec<bool>>,
+}
+
+impl<'a> Context<'a> {
+    fn new(mmtf: &'a Mmtf) -> Self {
+        let hierarchy = mmtf.hierarchy();
+        let atom_chain = (0..hierarchy.num_atoms())
+            .map(|atom| hierarchy.atom_chain(atom))
+            .collect();
+        let atom_model = (0..hierarchy.num_atoms())
+            .map(|atom| hierarchy.atom_model(atom))
+            .collect();
+
+        let polymer_chains = mmtf
+            .entity_list
+            .as_ref()
+            .filter(|entities| !entities.is_empty())
+            .map(|entities| {
+                let mut polymer_chains = vec![false; hierarchy.num_chains()];
+                for entity in entities {
+                    if entity.entity_type() == EntityType::Polymer {
+                        for chain in &entity.chain_index_list {
+                            if let Some(polymer) = polymer_chains.get_mut(*chain as usize) {
+                                *polymer = true;
+                            }
+                        }
+                    }
+                }
+                polymer_chains
+            });
+
+        Context {
+            mmtf,
+            hierarchy,
+            atom_chain,
+            atom_model,
+            elements: mmtf.atom_elements(),
+            polymer_chains,
+        }
+    }
+
+    fn num_atoms(&self) -> usize {
+        self.hierarchy.num_atoms()
+    }
+
+    fn group_type(&self, atom: usize) -> &'a GroupType {
+        let group_type = self.mmtf.group_type_list[self.hierarchy.atom_group[atom]];
+        &self.mmtf.group_list[group_type as usize]
+    }
+
+    fn atom_name(&self, atom: usize) -> &'a str {
+        let first = self.hierarchy.group_atoms[self.hierarchy.atom_group[atom]].start;
+        &self.group_type(atom).atom_name_list[atom - first]
+    }
+
+    fn chain_name(&self, atom: usize) -> &'a str {
+        let chain = self.atom_chain[atom];
+        match self.mmtf.chain_name_list {
+            Some(ref names) if chain < names.len() => &names[chain],
+            _ => &self.mmtf.chain_id_list[chain],
+        }
+    }
+
+    fn evaluate(&self, selection: &Selection) -> AtomSet {
+        let n = self.num_atoms();
+        let contains =
+            |values: &[String], value: &str| values.iter().any(|v| v.eq_ignore_ascii_case(value));
+        let in_ranges = |ranges: &[(i32, i32)], value: i32| {
+            ranges
+                .iter()
+                .any(|&(start, end)| start <= value && value <= end)
+        };
+
+        match *selection {
+            Selection::All => AtomSet::all(n),
+            Selection::None => AtomSet::empty(n),
+            Selection::Not(ref inner) => self.evaluate(inner).complement(),
+            Selection::And(ref a, ref b) => self.evaluate(a).intersection(&self.evaluate(b)),
+            Selection::Or(ref a, ref b) => self.evaluate(a).union(&self.evaluate(b)),
+            Selection::Chain(ref names) => AtomSet::from_fn(n, |atom| {
+                names.iter().any(|name| name == self.chain_name(atom))
+            }),
+            Selection::ResName(ref names) => {
+                AtomSet::from_fn(n, |atom| contains(names, &self.group_type(atom).group_name))
+            }
+            Selection::ResId(ref ranges) => AtomSet::from_fn(n, |atom| {
+                in_ranges(
+                    ranges,
+                    self.mmtf.group_id_list[self.hierarchy.atom_group[atom]],
+                )
+            }),
+            Selection::Name(ref names) => {
+                AtomSet::from_fn(n, |atom| contains(names, self.atom_name(atom)))
+            }
+            Selection::Element(ref elements) => AtomSet::from_fn(n, |atom| {
+                self.elements[atom].is_some_and(|e| elements.contains(&e))
+            }),
+            Selection::AltLoc(ref labels) => AtomSet::from_fn(n, |atom| {
+                let label = self
+                    .mmtf
+                    .alt_loc_list
+                    .as_ref()
+                    .and_then(|list| list.get(atom).cloned())
+                    .unwrap_or('\0');
+                let label = if label == ' ' { '\0' } else { label };
+                labels.contains(&label)
+            }),
+            Selection::Model(ref ranges) => AtomSet::from_fn(n, |atom| {
+                in_ranges(ranges, self.atom_model[atom] as i32 + 1)
+            }),
+            Selection::Index(ref ranges) => {
+                AtomSet::from_fn(n, |atom| in_ranges(ranges, atom as i32))
+            }
+            Selection::Within(distance, ref inner) => self.within(distance, &self.evaluate(inner)),
+            Selection::Polymer => AtomSet::from_fn(n, |atom| match self.polymer_chains {
+                Some(ref chains) => chains[self.atom_chain[atom]],
+                None => {
+                    let group_type = self.group_type(atom);
+                    group_type.is_amino_acid() || group_type.is_nucleotide()
+                }
+            }),
+            Selection::Protein => AtomSet::from_fn(n, |atom| self.group_type(atom).is_amino_acid()),
+            Selection::Nucleic => AtomSet::from_fn(n, |atom| self.group_type(atom).is_nucleotide()),
+            Selection::Water => AtomSet::from_fn(n, |atom| self.group_type(atom).is_water()),
+            Selection::Ligand => AtomSet::from_fn(n, |atom| self.group_type(atom).is_ligand()),
+            Selection::Hydrogen => {
+                AtomSet::from_fn(n, |atom| self.elements[atom] == Some(Element::H))
+            }
+            Selection::Backbone => {
+                let protein = Selection::And(
+                    Box::new(Selection::Protein),
+                    Box::new(Selection::Name(
+                        PROTEIN_BACKBONE.iter().map(|s| s.to_string()).collect(),
+                    )),
+                );
+                let nucleic = Selection::And(
+                    Box::new(Selection::Nucleic),
+                    Box::new(Selection::Name(
+                        NUCLEIC_BACKBONE.iter().map(|s| s.to_string()).collect(),
+                    )),
+                );
+                self.evaluate(&protein).union(&self.evaluate(&nucleic))
+            }
+        }
+    }
+
+    fn within(&self, distance: f32, targets: &AtomSet) -> AtomSet {
+        let search = NeighborSearch::with_atoms(self.mmtf, targets.iter(), distance);
+        AtomSet::from_fn(self.num_atoms(), |atom| {
+            search
+                .within(self.mmtf.position(atom), distance)
+                .iter()
+                .any(|&(other, _)| self.atom_model[atom] == self.atom_model[other])
+        })
+    }
+}
+
+impl Selection {
+    /// Parse a selection
+    pub fn parse(input: &str) -> Result<Selection, SelectionError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            position: 0,
+        };
+        let selection = parser.or()?;
+        if parser.position < parser.tokens.len() {
+            return Err(parser.unexpected());
+        }
+        Ok(selection)
+    }
+
+    /// Atoms of the structure matching the selection
+    pub fn evaluate(&self, mmtf: &Mmtf) -> AtomSet {
+        Context::new(mmtf).evaluate(self)
+    }
+}
+
+impl FromStr for Selection {
+    type Err = SelectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Selection::parse(s)
+    }
+}
+
+impl Mmtf {
+    /// Parse and evaluate a [`Selection`](../selection/enum.Selection.html)
+    pub fn select_atoms(&self, selection: &str) -> Result<AtomSet, SelectionError> {
+        Ok(Selection::parse(selection)?.evaluate(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    fn count(mmtf: &Mmtf, selection: &str) -> usize {
+        mmtf.select_atoms(selection).unwrap().len()
+    }
+
+    #[test]
+    fn it_combine_atom_sets() {
+        let a = AtomSet::from_indices(5, vec![0, 1, 2, 7]);
+        let b = AtomSet::from_indices(5, vec![2, 3]);
+        assert_eq!(vec![0, 1, 2], a.indices());
+        assert_eq!(vec![0, 1, 2, 3], a.union(&b).indices());
+        assert_eq!(vec![2], a.intersection(&b).indices());
+        assert_eq!(vec![0, 1], a.difference(&b).indices());
+        assert_eq!(vec![3, 4], a.complement().indices());
+        assert!(AtomSet::empty(5).is_empty());
+        assert_eq!(5, AtomSet::all(5).len());
+    }
+
+    #[test]
+    fn it_parse_selections() {
+        assert_eq!(
+            Selection::And(
+                Box::new(Selection::Chain(vec!["A".to_string(), "B".to_string()])),
+                Box::new(Selection::Not(Box::new(Selection::ResId(vec![
+                    (-5, 3),
+                    (10, 10)
+                ])))),
+            ),
+            Selection::parse("chain A+B AND not resi -5-3 10").unwrap()
+        );
+        assert_eq!(
+            Selection::Or(
+                Box::new(Selection::AltLoc(vec!['A'])),
+                Box::new(Selection::AltLoc(vec!['\0'])),
+            ),
+            "altloc A or altloc ''".parse().unwrap()
+        );
+        assert_eq!(
+            Selection::Within(4.5, Box::new(Selection::Element(vec![Element::Fe]))),
+            Selection::parse("within 4.5 of element FE").unwrap()
+        );
+    }
+
+    #[test]
+    fn it_report_parse_errors() {
+        assert_eq!(
+            Err(SelectionError::UnexpectedEnd),
+            Selection::parse("chain A and")
+        );
+        assert_eq!(
+            Err(SelectionError::UnexpectedEnd),
+            Selection::parse("(chain A")
+        );
+        assert_eq!(
+            Err(SelectionError::UnexpectedToken(")".to_string())),
+            Selection::parse("chain A)")
+        );
+        assert_eq!(
+            Err(SelectionError::UnexpectedToken("foo".to_string())),
+            Selection::parse("foo")
+        );
+        assert_eq!(
+            Err(SelectionError::InvalidNumber("x".to_string())),
+            Selection::parse("resi x")
+        );
+        assert_eq!(
+            Err(SelectionError::InvalidElement("Qq".to_string())),
+            Selection::parse("element Qq")
+        );
+    }
+
+    #[test]
+    fn it_select_atoms() {
+        let mmtf = load_173d();
+
+        // chain names are shared by the polymer and the water chains
+        assert_eq!(207, count(&mmtf, "chain A"));
+        assert_eq!(161, count(&mmtf, "chain A and polymer"));
+        assert_eq!(401, count(&mmtf, "chain A B"));
+        assert_eq!(111, count(&mmtf, "not (chain A or chain B)"));
+        assert_eq!(3, count(&mmtf, "chain A and resi 1-4 and name P"));
+        assert_eq!(96, count(&mmtf, "resn HOH"));
+        assert_eq!(96, count(&mmtf, "water"));
+        assert_eq!(26, count(&mmtf, "ligand"));
+        assert_eq!(14, count(&mmtf, "nucleic and name P"));
+        assert_eq!(16, count(&mmtf, "name \"O5'\""));
+        assert_eq!(416, count(&mmtf, "polymer and not hydrogen"));
+        assert_eq!(0, count(&mmtf, "element Fe"));
+        assert_eq!(512, count(&mmtf, "altloc ''"));
+        assert_eq!(512, count(&mmtf, "model 1"));
+        assert_eq!(0, count(&mmtf, "model 2"));
+        assert_eq!(10, count(&mmtf, "index 0-9"));
+    }
+
+    #[test]
+    fn it_select_polymer_without_entities() {
+        let mut mmtf = load_173d();
+        mmtf.entity_list = None;
+
+        assert_eq!(0, count(&mmtf, "polymer and (water or ligand)"));
+        assert_eq!(count(&mmtf, "protein or nucleic"), count(&mmtf, "polymer"));
+        assert!(count(&mmtf, "polymer") > 0);
+    }
+
+    #[test]
+    fn it_select_atoms_within_distance() {
+        let mmtf = load_173d();
+        let ligand = mmtf.select_atoms("resn PXZ").unwrap();
+        let near = mmtf.select_atoms("within 5 of resn PXZ").unwrap();
+
+        assert_eq!(ligand, ligand.intersection(&near));
+        assert_eq!(115, near.len());
+        for atom in near.iter() {
+            let distance = ligand
+                .iter()
+                .map(|other| {
+                    ((mmtf.x_coord_list[atom] - mmtf.x_coord_list[other]).powi(2)
+                        + (mmtf.y_coord_list[atom] - mmtf.y_coord_list[other]).powi(2)
+                        + (mmtf.z_coord_list[atom] - mmtf.z_coord_list[other]).powi(2))
+                    .sqrt()
+                })
+                .fold(f32::MAX, f32::min);
+            assert!(distance <= 5.0);
+        }
+    }
+}
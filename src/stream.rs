@@ -0,0 +1,306 @@
+use std::fmt;
+use std::io::Read;
+
+use decode::{EncodedVisitor, HeaderVisitor};
+use encode::{HeaderLayout, StrategyDataTypes};
+use mmtf::{BioAssembly, Entity, GroupType};
+use rmps::decode::Error;
+use rmps::Deserializer;
+use serde::de::{Deserializer as SerdeDeserializer, IgnoredAny, MapAccess, Visitor};
+
+/// Keys of the binary encoded fields, see the
+/// [MMTF specification](https://github.com/rcsb/mmtf/blob/master/spec.md#fields)
+const ENCODED_FIELDS: [&str; 16] = [
+    "bondAtomList",
+    "bondOrderList",
+    "xCoordList",
+    "yCoordList",
+    "zCoordList",
+    "bFactorList",
+    "atomIdList",
+    "altLocList",
+    "occupancyList",
+    "groupIdList",
+    "groupTypeList",
+    "secStructList",
+    "insCodeList",
+    "sequenceIndexList",
+    "chainIdList",
+    "chainNameList",
+];
+
+/// Value of a single top-level field of a `MMTF` file
+#[derive(Debug)]
+pub enum Field {
+    /// Decoded binary array, e.g. `xCoordList` or `groupTypeList`
+    Encoded(StrategyDataTypes),
+
+    /// Text, e.g. `title` or `spaceGroup`
+    Text(String),
+
+    /// List of texts, `experimentalMethods`
+    Texts(Vec<String>),
+
+    /// Integer, e.g. `numAtoms`
+    Integer(i32),
+
+    /// List of integers, `groupsPerChain` and `chainsPerModel`
+    Integers(Vec<i32>),
+
+    /// Floating-point number, `resolution`, `rFree` and `rWork`
+    Float(f64),
+
+    /// List of floating-point numbers, `unitCell`
+    Floats(Vec<f64>),
+
+    /// `ncsOperatorList`
+    NcsOperators(Vec<[f32; 16]>),
+
+    /// `bioAssemblyList`
+    BioAssemblies(Vec<BioAssembly>),
+
+    /// `entityList`
+    Entities(Vec<Entity>),
+
+    /// `groupList`
+    Groups(Vec<GroupType>),
+}
+
+/// Decodes a `MMTF` file one field at a time
+///
+/// In contrast to [`Mmtf::from`](../mmtf/struct.Mmtf.html#method.from) the
+/// fields are handed to a callback as soon as they are decoded, so they can
+/// be processed or dropped without holding the whole structure in memory.
+/// Fields which are not requested are skipped without being decoded.
+///
+/// # Examples
+///
+/// ```
+/// # use std::path::Path;
+/// # use std::env;
+/// use std::fs::File;
+/// use mmtf::encode::StrategyDataTypes;
+/// use mmtf::stream::{Field, StreamDecoder};
+///
+/// # let file_path = Path::new(&env::current_dir().unwrap())
+/// #                                    .join("tests")
+/// #                                    .join("data")
+/// #                                    .join("173D.mmtf");
+/// let file = File::open(&file_path).unwrap();
+///
+/// let mut num_atoms = 0;
+/// StreamDecoder::new(file)
+///     .fields(&["xCoordList"])
+///     .for_each(|name, field| {
+///         if let Field::Encoded(StrategyDataTypes::VecFloat32(x)) = field {
+///             assert_eq!("xCoordList", name);
+///             num_atoms = x.len();
+///         }
+///     })
+///     .unwrap();
+/// assert_eq!(512, num_atoms);
+/// ```
+#[derive(Debug)]
+pub struct StreamDecoder<R> {
+    reader: R,
+    fields: Option<Vec<String>>,
+}
+
+impl<R: Read> StreamDecoder<R> {
+    /// Decoder of all fields of the given reader
+    pub fn new(reader: R) -> Self {
+        StreamDecoder {
+            reader,
+            fields: None,
+        }
+    }
+
+    /// Only decode the fields with the given keys, e.g. `"xCoordList"`
+    pub fn fields(mut self, names: &[&str]) -> Self {
+        self.fields = Some(names.iter().map(|name| name.to_string()).collect());
+        self
+    }
+
+    /// Call `callback` with the key and value of each decoded field, in
+    /// the order of the file
+    ///
+    /// Unknown fields are skipped.
+    pub fn for_each<F>(self, mut callback: F) -> Result<(), Error>
+    where
+        F: FnMut(&str, Field),
+    {
+        let mut de = Deserializer::new(self.reader);
+        let visitor = FieldVisitor {
+            fields: self.fields.as_ref().map(|fields| &fields[..]),
+            callback: &mut callback,
+        };
+        (&mut de).deserialize_map(visitor)
+    }
+
+    /// Codec headers of the binary encoded fields, in the order of the file
+    ///
+    /// Only the twelve header bytes of each field are interpreted, the data
+    /// is skipped without being decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::stream::StreamDecoder;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let file = File::open(&file_path).unwrap();
+    /// let headers = StreamDecoder::new(file)
+    ///     .fields(&["xCoordList"])
+    ///     .headers()
+    ///     .unwrap();
+    ///
+    /// assert_eq!("xCoordList", headers[0].0);
+    /// assert_eq!(10, headers[0].1.codec);
+    /// assert_eq!(1000, headers[0].1.parameter);
+    /// ```
+    pub fn headers(self) -> Result<Vec<(String, HeaderLayout)>, Error> {
+        let mut de = Deserializer::new(self.reader);
+        let visitor = HeadersVisitor {
+            fields: self.fields.as_ref().map(|fields| &fields[..]),
+        };
+        (&mut de).deserialize_map(visitor)
+    }
+}
+
+struct HeadersVisitor<'a> {
+    fields: Option<&'a [String]>,
+}
+
+impl<'a, 'de> Visitor<'de> for HeadersVisitor<'a> {
+    type Value = Vec<(String, HeaderLayout)>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("map of MMTF fields")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut headers = Vec::new();
+        while let Some(name) = map.next_key::<String>()? {
+            let wanted = self.fields.is_none_or(|fields| fields.contains(&name));
+            if wanted && ENCODED_FIELDS.contains(&name.as_str()) {
+                let header = map.next_value_seed(HeaderVisitor)?;
+                headers.push((name, header));
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(headers)
+    }
+}
+
+struct FieldVisitor<'a, F: 'a> {
+    fields: Option<&'a [String]>,
+    callback: &'a mut F,
+}
+
+impl<'a, 'de, F> Visitor<'de> for FieldVisitor<'a, F>
+where
+    F: FnMut(&str, Field),
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("map of MMTF fields")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(name) = map.next_key::<String>()? {
+            if let Some(fields) = self.fields {
+                if !fields.contains(&name) {
+                    map.next_value::<IgnoredAny>()?;
+                    continue;
+                }
+            }
+            let field = match name.as_str() {
+                name if ENCODED_FIELDS.contains(&name) => {
+                    Field::Encoded(map.next_value_seed(EncodedVisitor)?)
+                }
+                "mmtfVersion" | "mmtfProducer" | "spaceGroup" | "structureId" | "title"
+                | "depositionDate" | "releaseDate" => Field::Text(map.next_value()?),
+                "experimentalMethods" => Field::Texts(map.next_value()?),
+                "numBonds" | "numAtoms" | "numGroups" | "numChains" | "numModels" => {
+                    Field::Integer(map.next_value()?)
+                }
+                "groupsPerChain" | "chainsPerModel" => Field::Integers(map.next_value()?),
+                "resolution" | "rFree" | "rWork" => Field::Float(map.next_value()?),
+                "unitCell" => Field::Floats(map.next_value()?),
+                "ncsOperatorList" => Field::NcsOperators(map.next_value()?),
+                "bioAssemblyList" => Field::BioAssemblies(map.next_value()?),
+                "entityList" => Field::Entities(map.next_value()?),
+                "groupList" => Field::Groups(map.next_value()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                    continue;
+                }
+            };
+            (self.callback)(&name, field);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::{load_173d, open_173d};
+
+    #[test]
+    fn it_stream_all_fields() {
+        let mmtf = load_173d();
+        let mut names = Vec::new();
+        StreamDecoder::new(open_173d())
+            .for_each(|name, field| {
+                match (name, field) {
+                    ("title", Field::Text(title)) => assert_eq!(mmtf.title, Some(title)),
+                    ("numAtoms", Field::Integer(n)) => assert_eq!(mmtf.num_atoms, n),
+                    ("groupList", Field::Groups(groups)) => assert_eq!(mmtf.group_list, groups),
+                    ("chainIdList", Field::Encoded(StrategyDataTypes::VecString(ids))) => {
+                        assert_eq!(mmtf.chain_id_list, ids)
+                    }
+                    ("zCoordList", Field::Encoded(StrategyDataTypes::VecFloat32(z))) => {
+                        assert_eq!(mmtf.z_coord_list, z)
+                    }
+                    _ => {}
+                }
+                names.push(name.to_string());
+            })
+            .unwrap();
+
+        for name in &[
+            "title",
+            "numAtoms",
+            "groupList",
+            "chainIdList",
+            "zCoordList",
+        ] {
+            assert!(names.iter().any(|n| n == name), "missing {}", name);
+        }
+    }
+
+    #[test]
+    fn it_skip_unrequested_fields() {
+        let mut names = Vec::new();
+        StreamDecoder::new(open_173d())
+            .fields(&["numModels", "groupTypeList"])
+            .for_each(|name, _| names.push(name.to_string()))
+            .unwrap();
+        assert_eq!(vec!["numModels", "groupTypeList"], names);
+    }
+
+    #[test]
+    fn it_fail_on_invalid_data() {
+        let data: &[u8] = &[0x81, 0xa4, b't', b'e', b's', b't'];
+        assert!(StreamDecoder::new(data).for_each(|_, _| {}).is_err());
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::BTreeMap;
+
+use bonds::count_bonds;
+use mmtf::{BioAssembly, Entity, GroupType, Mmtf, Transform};
+use selection::AtomSet;
+
+/// Keep the entries of `list` whose index is selected in `keep`
+fn filter<T: Clone>(list: &[T], keep: &[bool]) -> Vec<T> {
+    list.iter()
+        .zip(keep)
+        .filter(|&(_, keep)| *keep)
+        .map(|(value, _)| value.clone())
+        .collect()
+}
+
+/// Map old indices to new indices, `None` for dropped entries
+fn reindex(keep: &[bool]) -> Vec<Option<usize>> {
+    let mut next = 0;
+    keep.iter()
+        .map(|keep| {
+            if *keep {
+                next += 1;
+                Some(next - 1)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Remap a list of chain indices, dropping the removed chains
+fn remap_chains(chains: &[i32], chain_map: &[Option<usize>]) -> Vec<i32> {
+    chains
+        .iter()
+        .filter_map(|chain| chain_map.get(*chain as usize).cloned().and_then(|c| c))
+        .map(|chain| chain as i32)
+        .collect()
+}
+
+/// Restrict a group type to the given local atom indices
+fn restrict_group_type(group_type: &GroupType, atoms: &[usize]) -> GroupType {
+    let mut local = vec![None; group_type.atom_name_list.len()];
+    for (new, old) in atoms.iter().enumerate() {
+        local[*old] = Some(new as i32);
+    }
+
+    let mut bond_atom_list = Vec::new();
+    let mut bond_order_list = Vec::new();
+    for (index, pair) in group_type.bond_atom_list.chunks(2).enumerate() {
+        if pair.len() != 2 {
+            continue;
+        }
+        if let (Some(a), Some(b)) = (local[pair[0] as usize], local[pair[1] as usize]) {
+            bond_atom_list.push(a);
+            bond_atom_list.push(b);
+            if let Some(order) = group_type.bond_order_list.get(index) {
+                bond_order_list.push(*order);
+            }
+        }
+    }
+
+    let pick = |list: &[String]| atoms.iter().map(|a| list[*a].clone()).collect();
+    GroupType {
+        formal_charge_list: atoms
+            .iter()
+            .map(|a| group_type.formal_charge_list[*a])
+            .collect(),
+        atom_name_list: pick(&group_type.atom_name_list),
+        element_list: group_type.element_list.as_ref().map(|list| pick(list)),
+        bond_atom_list,
+        bond_order_list,
+        group_name: group_type.group_name.clone(),
+        single_letter_code: group_type.single_letter_code.clone(),
+        chem_comp_type: group_type.chem_comp_type.clone(),
+    }
+}
+
+impl Mmtf {
+    /// Secondary structure for every group, expanding lists which are only
+    /// given for the first model to all models.
+    pub(crate) fn sec_struct_per_group(&self) -> Option<Vec<i8>> {
+        let list = self.sec_struct_list.as_ref()?;
+        let num_groups = self.group_type_list.len();
+        if list.len() == num_groups {
+            return Some(list.clone());
+        }
+
+        let hierarchy = self.hierarchy();
+        let mut expanded = Vec::with_capacity(num_groups);
+        for model in 0..hierarchy.num_models() {
+            let groups = hierarchy.model_groups(model);
+            expanded.extend((0..groups.len()).map(|i| list.get(i).cloned().unwrap_or(-1)));
+        }
+        Some(expanded)
+    }
+
+    /// Extract the selected atoms into a new, self-contained structure.
+    ///
+    /// Groups, chains and models without any selected atom are removed.
+    /// Groups which are only partially selected get a new [`GroupType`](../mmtf/struct.GroupType.html)
+    /// holding the selected atoms and the bonds between them. Unused group types
+    /// are pruned, inter-group bonds are kept when both atoms are selected, and
+    /// entities and bio assemblies are updated to the remaining chains.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    ///
+    /// let atoms = mmtf.select_atoms("chain C and polymer").unwrap();
+    /// let chain = mmtf.select(&atoms);
+    ///
+    /// assert_eq!(1, chain.num_chains);
+    /// assert_eq!(6, chain.num_groups);
+    /// assert_eq!(atoms.len() as i32, chain.num_atoms);
+    /// ```
+    pub fn select(&self, atoms: &AtomSet) -> Mmtf {
+        let hierarchy = self.hierarchy();
+        let keep_atoms: Vec<bool> = (0..hierarchy.num_atoms())
+            .map(|atom| atoms.contains(atom))
+            .collect();
+        let atom_map = reindex(&keep_atoms);
+
+        // groups and their (possibly restricted) group types, keeping the
+        // order of the original group types
+        let mut keep_groups = vec![false; hierarchy.num_groups()];
+        let mut group_keys = Vec::new();
+        let mut type_map: BTreeMap<(usize, Vec<usize>), i32> = BTreeMap::new();
+        for (group, range) in hierarchy.group_atoms.iter().enumerate() {
+            let local: Vec<usize> = range
+                .clone()
+                .filter(|atom| keep_atoms[*atom])
+                .map(|atom| atom - range.start)
+                .collect();
+            if local.is_empty() {
+                continue;
+            }
+            keep_groups[group] = true;
+            let key = (self.group_type_list[group] as usize, local);
+            type_map.insert(key.clone(), 0);
+            group_keys.push(key);
+        }
+
+        let mut group_types = Vec::with_capacity(type_map.len());
+        for (&(old_type, ref atoms), new_type) in &mut type_map {
+            let original = &self.group_list[old_type];
+            if atoms.len() == original.atom_name_list.len() {
+                group_types.push(original.clone());
+            } else {
+                group_types.push(restrict_group_type(original, atoms));
+            }
+            *new_type = group_types.len() as i32 - 1;
+        }
+        let group_type_list: Vec<i32> = group_keys.iter().map(|key| type_map[key]).collect();
+
+        let mut groups_per_chain = vec![0; hierarchy.num_chains()];
+        for (group, keep) in keep_groups.iter().enumerate() {
+            if *keep {
+                groups_per_chain[hierarchy.group_chain[group]] += 1;
+            }
+        }
+        let keep_chains: Vec<bool> = groups_per_chain.iter().map(|n| *n > 0).collect();
+        let chain_map = reindex(&keep_chains);
+
+        let mut chains_per_model = vec![0; hierarchy.num_models()];
+        for (chain, keep) in keep_chains.iter().enumerate() {
+            if *keep {
+                chains_per_model[hierarchy.chain_model[chain]] += 1;
+            }
+        }
+        chains_per_model.retain(|n| *n > 0);
+        groups_per_chain.retain(|n| *n > 0);
+
+        // inter-group bonds between selected atoms
+        let mut bond_atom_list = Vec::new();
+        let mut bond_order_list = self.bond_order_list.as_ref().map(|_| Vec::new());
+        for (index, pair) in self.bond_atom_list.chunks(2).enumerate() {
+            if pair.len() != 2 {
+                continue;
+            }
+            let a = atom_map.get(pair[0] as usize).cloned().and_then(|a| a);
+            let b = atom_map.get(pair[1] as usize).cloned().and_then(|b| b);
+            if let (Some(a), Some(b)) = (a, b) {
+                bond_atom_list.push(a as i32);
+                bond_atom_list.push(b as i32);
+                if let Some(ref mut orders) = bond_order_list {
+                    let order = self.bond_order_list.as_ref().and_then(|o| o.get(index));
+                    orders.push(order.cloned().unwrap_or(1));
+                }
+            }
+        }
+
+        let entity_list = self.entity_list.as_ref().map(|entities| {
+            entities
+                .iter()
+                .map(|entity| Entity {
+                    chain_index_list: remap_chains(&entity.chain_index_list, &chain_map),
+                    ..entity.clone()
+                })
+                .filter(|entity| !entity.chain_index_list.is_empty())
+                .collect()
+        });
+
+        let bio_assembly_list = self.bio_assembly_list.as_ref().map(|assemblies| {
+            assemblies
+                .iter()
+                .map(|assembly| BioAssembly {
+                    transform_list: assembly
+                        .transform_list
+                        .iter()
+                        .map(|transform| Transform {
+                            chain_index_list: remap_chains(&transform.chain_index_list, &chain_map),
+                            matrix: transform.matrix.clone(),
+                        })
+                        .filter(|transform| !transform.chain_index_list.is_empty())
+                        .collect(),
+                    name: assembly.name.clone(),
+                })
+                .filter(|assembly: &BioAssembly| !assembly.transform_list.is_empty())
+                .collect()
+        });
+
+        let mut mmtf = Mmtf {
+            mmtf_version: self.mmtf_version.clone(),
+            mmtf_producer: self.mmtf_producer.clone(),
+            unit_cell: self.unit_cell.clone(),
+            space_group: self.space_group.clone(),
+            structure_id: self.structure_id.clone(),
+            title: self.title.clone(),
+            deposition_date: self.deposition_date.clone(),
+            release_date: self.release_date.clone(),
+            ncs_operator_list: self.ncs_operator_list.clone(),
+            bio_assembly_list,
+            entity_list,
+            experimental_methods: self.experimental_methods.clone(),
+            resolution: self.resolution,
+            r_free: self.r_free,
+            r_work: self.r_work,
+            num_bonds: 0,
+            num_atoms: atom_map.iter().filter(|a| a.is_some()).count() as i32,
+            num_groups: group_type_list.len() as i32,
+            num_chains: groups_per_chain.len() as i32,
+            num_models: chains_per_model.len() as i32,
+            group_list: group_types,
+            bond_atom_list,
+            bond_order_list,
+            x_coord_list: filter(&self.x_coord_list, &keep_atoms),
+            y_coord_list: filter(&self.y_coord_list, &keep_atoms),
+            z_coord_list: filter(&self.z_coord_list, &keep_atoms),
+            b_factor_list: self.b_factor_list.as_ref().map(|l| filter(l, &keep_atoms)),
+            atom_id_list: self.atom_id_list.as_ref().map(|l| filter(l, &keep_atoms)),
+            alt_loc_list: self.alt_loc_list.as_ref().map(|l| filter(l, &keep_atoms)),
+            occupancy_list: self.occupancy_list.as_ref().map(|l| filter(l, &keep_atoms)),
+            group_id_list: filter(&self.group_id_list, &keep_groups),
+            group_type_list,
+            sec_struct_list: self
+                .sec_struct_per_group()
+                .map(|l| filter(&l, &keep_groups)),
+            ins_code_list: self.ins_code_list.as_ref().map(|l| filter(l, &keep_groups)),
+            sequence_index_list: self
+                .sequence_index_list
+                .as_ref()
+                .map(|l| filter(l, &keep_groups)),
+            chain_id_list: filter(&self.chain_id_list, &keep_chains),
+            chain_name_list: self
+                .chain_name_list
+                .as_ref()
+                .map(|l| filter(l, &keep_chains)),
+            groups_per_chain,
+            chains_per_model,
+        };
+        mmtf.num_bonds = count_bonds(&mmtf);
+        mmtf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    #[test]
+    fn it_reindex_kept_entries() {
+        assert_eq!(
+            vec![Some(0), None, Some(1), None],
+            reindex(&[true, false, true, false])
+        );
+        assert_eq!(
+            vec![2, 4],
+            filter(&[1, 2, 3, 4], &[false, true, false, true])
+        );
+        assert_eq!(vec![1], remap_chains(&[0, 2], &[None, Some(0), Some(1)]));
+    }
+
+    #[test]
+    fn it_select_everything() {
+        let mmtf = load_173d();
+        let copy = mmtf.select(&AtomSet::all(512));
+
+        assert_eq!(mmtf.num_atoms, copy.num_atoms);
+        assert_eq!(mmtf.num_bonds, copy.num_bonds);
+        assert_eq!(mmtf.group_type_list, copy.group_type_list);
+        assert_eq!(mmtf.bond_atom_list, copy.bond_atom_list);
+        assert_eq!(mmtf.sec_struct_list, copy.sec_struct_list);
+        assert_eq!(mmtf.groups_per_chain, copy.groups_per_chain);
+        assert_eq!(mmtf.x_coord_list, copy.x_coord_list);
+    }
+
+    #[test]
+    fn it_select_nothing() {
+        let mmtf = load_173d();
+        let empty = mmtf.select(&AtomSet::empty(512));
+
+        assert_eq!(0, empty.num_atoms);
+        assert_eq!(0, empty.num_models);
+        assert!(empty.group_list.is_empty());
+        assert_eq!(Some(0), empty.entity_list.map(|e| e.len()));
+        assert_eq!(Some(0), empty.bio_assembly_list.map(|b| b.len()));
+    }
+
+    #[test]
+    fn it_select_single_chain() {
+        let mmtf = load_173d();
+        let chain = mmtf.select(&mmtf.select_atoms("chain C and polymer").unwrap());
+
+        assert_eq!(vec!["C".to_string()], chain.chain_id_list);
+        assert_eq!(vec![6], chain.groups_per_chain);
+        assert_eq!(vec![1], chain.chains_per_model);
+        assert_eq!(Some(vec![0, 1, 2, 3, 4, 5]), chain.sequence_index_list);
+        assert_eq!(6, chain.group_list.len());
+        assert_eq!(chain.num_bonds, chain.bond_graph().num_bonds() as i32);
+
+        let entities = chain.entity_list.unwrap();
+        assert_eq!(1, entities.len());
+        assert_eq!("ACTINOMYCIN D", entities[0].description);
+        assert_eq!(vec![0], entities[0].chain_index_list);
+
+        let assemblies = chain.bio_assembly_list.unwrap();
+        assert_eq!(1, assemblies.len());
+        assert_eq!("2", assemblies[0].name);
+        assert_eq!(vec![0], assemblies[0].transform_list[0].chain_index_list);
+    }
+
+    #[test]
+    fn it_restrict_partial_groups() {
+        let mmtf = load_173d();
+        let atoms = mmtf
+            .select_atoms("backbone and chain A and polymer")
+            .unwrap();
+        let backbone = mmtf.select(&atoms);
+
+        assert_eq!(atoms.len() as i32, backbone.num_atoms);
+        assert_eq!(8, backbone.num_groups);
+        for group_type in &backbone.group_list {
+            assert!(group_type.atom_name_list.len() <= 8);
+            assert_eq!(
+                group_type.atom_name_list.len(),
+                group_type.formal_charge_list.len()
+            );
+            assert!(group_type
+                .bond_atom_list
+                .iter()
+                .all(|a| (*a as usize) < group_type.atom_name_list.len()));
+        }
+
+        // the backbone of the strand is still one connected molecule
+        let graph = backbone.bond_graph();
+        assert_eq!(1, graph.connected_components().len());
+        assert_eq!(backbone.num_bonds as usize, graph.num_bonds());
+    }
+
+    #[test]
+    fn it_keep_inter_group_bonds() {
+        let mmtf = load_173d();
+        let pocket = mmtf.select(
+            &mmtf
+                .select_atoms("resi 1-2 and chain A and polymer")
+                .unwrap(),
+        );
+
+        // the phosphodiester bond between the two nucleotides
+        assert_eq!(2, pocket.bond_atom_list.len());
+        assert_eq!(Some(1), pocket.bond_order_list.map(|o| o.len()));
+    }
+}
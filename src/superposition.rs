@@ -0,0 +1,505 @@
+use hierarchy::Hierarchy;
+use mmtf::Mmtf;
+use selection::AtomSet;
+
+/// Weights of the atom pairs of a superposition
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Weighting {
+    /// All atom pairs are weighted equally
+    #[default]
+    Uniform,
+
+    /// Atom pairs are weighted by the mass of the element of the mobile atom
+    Mass,
+
+    /// Atom pairs are weighted by the inverse of their mean b-factor, so that
+    /// well ordered atoms dominate the fit
+    BFactor,
+}
+
+/// Rigid body transformation of a superposition and the resulting RMSD
+#[derive(Debug, Clone, PartialEq)]
+pub struct Superposition {
+    /// Rotation matrix, applied before the translation
+    pub rotation: [[f64; 3]; 3],
+
+    /// Translation vector
+    pub translation: [f64; 3],
+
+    /// Weighted root mean square deviation of the superimposed atom pairs
+    pub rmsd: f64,
+}
+
+impl Superposition {
+    /// Transform a single point
+    pub fn apply(&self, point: [f64; 3]) -> [f64; 3] {
+        let mut result = self.translation;
+        for (i, row) in self.rotation.iter().enumerate() {
+            result[i] += row[0] * point[0] + row[1] * point[1] + row[2] * point[2];
+        }
+        result
+    }
+
+    /// Transform all coordinates of the given structure in place
+    pub fn apply_to(&self, mmtf: &mut Mmtf) {
+        for atom in 0..mmtf.x_coord_list.len() {
+            let point = self.apply(to_f64(mmtf.position(atom)));
+            mmtf.x_coord_list[atom] = point[0] as f32;
+            mmtf.y_coord_list[atom] = point[1] as f32;
+            mmtf.z_coord_list[atom] = point[2] as f32;
+        }
+    }
+}
+
+/// Atom [position](../mmtf/struct.Mmtf.html#method.position) in double
+/// precision, for the sums of fits and geometric descriptors
+pub(crate) fn to_f64(point: [f32; 3]) -> [f64; 3] {
+    [
+        f64::from(point[0]),
+        f64::from(point[1]),
+        f64::from(point[2]),
+    ]
+}
+
+/// Weighted centroid of the given points
+fn centroid(points: &[[f64; 3]], weights: &[f64]) -> [f64; 3] {
+    let total: f64 = weights.iter().sum();
+    let mut center = [0.0; 3];
+    for (point, weight) in points.iter().zip(weights) {
+        for k in 0..3 {
+            center[k] += point[k] * weight / total;
+        }
+    }
+    center
+}
+
+/// Eigenvalues and eigenvectors of a symmetric matrix, computed with cyclic
+/// Jacobi rotations. The eigenvector of the `i`-th eigenvalue is the `i`-th
+/// column of the returned matrix.
+pub(crate) fn symmetric_eigen<const N: usize>(mut a: [[f64; N]; N]) -> ([f64; N], [[f64; N]; N]) {
+    let mut v = [[0.0; N]; N];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for _ in 0..50 {
+        let off: f64 = (0..N)
+            .flat_map(|p| (p + 1..N).map(move |q| (p, q)))
+            .map(|(p, q)| a[p][q] * a[p][q])
+            .sum();
+        if off < 1e-22 {
+            break;
+        }
+        for p in 0..N {
+            for q in p + 1..N {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (row_p, row_q) = (a[p], a[q]);
+                for (k, (apk, aqk)) in row_p.iter().zip(&row_q).enumerate() {
+                    a[p][k] = c * apk - s * aqk;
+                    a[q][k] = s * apk + c * aqk;
+                }
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut values = [0.0; N];
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = a[i][i];
+    }
+    (values, v)
+}
+
+/// Eigenvector of the largest eigenvalue of a symmetric 4x4 matrix
+fn largest_eigenvector(a: [[f64; 4]; 4]) -> (f64, [f64; 4]) {
+    let (values, v) = symmetric_eigen(a);
+    let largest = (0..4)
+        .max_by(|i, j| values[*i].total_cmp(&values[*j]))
+        .unwrap();
+    (
+        values[largest],
+        [v[0][largest], v[1][largest], v[2][largest], v[3][largest]],
+    )
+}
+
+/// Superimpose `mobile` onto `target` by minimizing the weighted RMSD
+///
+/// The optimal rotation is found with the quaternion method of Horn, which is
+/// equivalent to the Kabsch algorithm but always gives a proper rotation.
+/// Returns `None` if the point lists are empty, differ in length or contain
+/// coordinates or weights which are not finite.
+///
+/// # Examples
+///
+/// ```
+/// use mmtf::superposition::superpose;
+///
+/// let mobile = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 2.0, 0.0]];
+/// let target = [[1.0, 1.0, 1.0], [1.0, 2.0, 1.0], [-1.0, 1.0, 1.0]];
+/// let fit = superpose(&mobile, &target, None).unwrap();
+///
+/// assert!(fit.rmsd < 1e-6);
+/// let moved = fit.apply([1.0, 0.0, 0.0]);
+/// assert!((moved[1] - 2.0).abs() < 1e-6);
+/// ```
+pub fn superpose(
+    mobile: &[[f64; 3]],
+    target: &[[f64; 3]],
+    weights: Option<&[f64]>,
+) -> Option<Superposition> {
+    if mobile.is_empty() || mobile.len() != target.len() {
+        return None;
+    }
+    let uniform = vec![1.0; mobile.len()];
+    let weights = weights.unwrap_or(&uniform);
+    let total: f64 = weights.iter().sum();
+    if weights.len() != mobile.len() || total <= 0.0 {
+        return None;
+    }
+    let finite = |points: &[[f64; 3]]| points.iter().flatten().all(|value| value.is_finite());
+    if !finite(mobile) || !finite(target) || !weights.iter().all(|w| w.is_finite()) {
+        return None;
+    }
+
+    let (mobile_center, target_center) = (centroid(mobile, weights), centroid(target, weights));
+    let mut s = [[0.0; 3]; 3];
+    let mut inner = 0.0;
+    for ((x, y), w) in mobile.iter().zip(target).zip(weights) {
+        let x = [
+            x[0] - mobile_center[0],
+            x[1] - mobile_center[1],
+            x[2] - mobile_center[2],
+        ];
+        let y = [
+            y[0] - target_center[0],
+            y[1] - target_center[1],
+            y[2] - target_center[2],
+        ];
+        for i in 0..3 {
+            for j in 0..3 {
+                s[i][j] += w * x[i] * y[j];
+            }
+            inner += w * (x[i] * x[i] + y[i] * y[i]);
+        }
+    }
+
+    #[rustfmt::skip]
+    let n = [
+        [s[0][0] + s[1][1] + s[2][2], s[1][2] - s[2][1], s[2][0] - s[0][2], s[0][1] - s[1][0]],
+        [s[1][2] - s[2][1], s[0][0] - s[1][1] - s[2][2], s[0][1] + s[1][0], s[2][0] + s[0][2]],
+        [s[2][0] - s[0][2], s[0][1] + s[1][0], -s[0][0] + s[1][1] - s[2][2], s[1][2] + s[2][1]],
+        [s[0][1] - s[1][0], s[2][0] + s[0][2], s[1][2] + s[2][1], -s[0][0] - s[1][1] + s[2][2]],
+    ];
+    let (eigenvalue, q) = largest_eigenvector(n);
+
+    #[rustfmt::skip]
+    let rotation = [
+        [
+            q[0] * q[0] + q[1] * q[1] - q[2] * q[2] - q[3] * q[3],
+            2.0 * (q[1] * q[2] - q[0] * q[3]),
+            2.0 * (q[1] * q[3] + q[0] * q[2]),
+        ],
+        [
+            2.0 * (q[1] * q[2] + q[0] * q[3]),
+            q[0] * q[0] - q[1] * q[1] + q[2] * q[2] - q[3] * q[3],
+            2.0 * (q[2] * q[3] - q[0] * q[1]),
+        ],
+        [
+            2.0 * (q[1] * q[3] - q[0] * q[2]),
+            2.0 * (q[2] * q[3] + q[0] * q[1]),
+            q[0] * q[0] - q[1] * q[1] - q[2] * q[2] + q[3] * q[3],
+        ],
+    ];
+
+    let mut translation = target_center;
+    for (i, row) in rotation.iter().enumerate() {
+        for k in 0..3 {
+            translation[i] -= row[k] * mobile_center[k];
+        }
+    }
+
+    Some(Superposition {
+        rotation,
+        translation,
+        rmsd: ((inner - 2.0 * eigenvalue) / total).max(0.0).sqrt(),
+    })
+}
+
+/// Weighted RMSD of two point lists without superposition, `None` if the lists
+/// are empty or differ in length from each other or from the weights
+pub fn rmsd(a: &[[f64; 3]], b: &[[f64; 3]], weights: Option<&[f64]>) -> Option<f64> {
+    if a.is_empty() || a.len() != b.len() {
+        return None;
+    }
+    let uniform = vec![1.0; a.len()];
+    let weights = weights.unwrap_or(&uniform);
+    let total: f64 = weights.iter().sum();
+    if weights.len() != a.len() || total <= 0.0 {
+        return None;
+    }
+    let sum: f64 = a
+        .iter()
+        .zip(b)
+        .zip(weights)
+        .map(|((a, b), w)| {
+            w * ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2))
+        })
+        .sum();
+    Some((sum / total).sqrt())
+}
+
+/// Pair the atoms of two selections in index order, `None` if they differ in size
+pub fn pair_atoms(mobile: &AtomSet, target: &AtomSet) -> Option<Vec<(usize, usize)>> {
+    if mobile.len() != target.len() {
+        return None;
+    }
+    Some(mobile.iter().zip(target.iter()).collect())
+}
+
+impl Mmtf {
+    /// Pair the atoms with the same name in corresponding groups, for example
+    /// group pairs from a [sequence alignment](../chain/struct.Chain.html#method.align_observed).
+    ///
+    /// Only atoms named in `names` are paired, all common atoms if it is empty.
+    pub fn pair_group_atoms(
+        &self,
+        other: &Mmtf,
+        groups: &[(usize, usize)],
+        names: &[&str],
+    ) -> Vec<(usize, usize)> {
+        let (hierarchy, other_hierarchy) = (Hierarchy::new(self), Hierarchy::new(other));
+        let atom_names = |mmtf: &Mmtf, hierarchy: &Hierarchy, group: usize| {
+            let group_type = &mmtf.group_list[mmtf.group_type_list[group] as usize];
+            let start = hierarchy.group_atoms[group].start;
+            group_type
+                .atom_name_list
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (start + i, name.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut pairs = Vec::new();
+        for &(group, other_group) in groups {
+            let other_atoms = atom_names(other, &other_hierarchy, other_group);
+            for (atom, name) in atom_names(self, &hierarchy, group) {
+                if !names.is_empty() && !names.contains(&name.as_str()) {
+                    continue;
+                }
+                if let Some(&(other_atom, _)) = other_atoms.iter().find(|(_, n)| *n == name) {
+                    pairs.push((atom, other_atom));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Superimpose the atoms of this structure onto the paired atoms of `target`.
+    ///
+    /// The structure is not modified, use [`Superposition::apply_to`](../superposition/struct.Superposition.html#method.apply_to)
+    /// to move its coordinates.
+    pub fn superpose_onto(
+        &self,
+        target: &Mmtf,
+        pairs: &[(usize, usize)],
+        weighting: Weighting,
+    ) -> Option<Superposition> {
+        let mobile: Vec<[f64; 3]> = pairs
+            .iter()
+            .map(|&(a, _)| to_f64(self.position(a)))
+            .collect();
+        let fixed: Vec<[f64; 3]> = pairs
+            .iter()
+            .map(|&(_, b)| to_f64(target.position(b)))
+            .collect();
+
+        let weights: Option<Vec<f64>> = match weighting {
+            Weighting::Uniform => None,
+            Weighting::Mass => {
+                let elements = self.atom_elements();
+                Some(
+                    pairs
+                        .iter()
+                        .map(|&(a, _)| elements[a].map_or(1.0, |e| f64::from(e.mass())))
+                        .collect(),
+                )
+            }
+            Weighting::BFactor => {
+                let b_factor = |mmtf: &Mmtf, atom: usize| {
+                    mmtf.b_factor_list
+                        .as_ref()
+                        .map_or(1.0, |list| f64::from(list[atom]))
+                };
+                Some(
+                    pairs
+                        .iter()
+                        .map(|&(a, b)| 2.0 / (b_factor(self, a) + b_factor(target, b)).max(2.0))
+                        .collect(),
+                )
+            }
+        };
+        superpose(&mobile, &fixed, weights.as_ref().map(|w| &w[..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    fn rotate_z(point: [f64; 3], angle: f64) -> [f64; 3] {
+        let (sin, cos) = angle.sin_cos();
+        [
+            cos * point[0] - sin * point[1],
+            sin * point[0] + cos * point[1],
+            point[2],
+        ]
+    }
+
+    #[test]
+    fn it_recover_rigid_motion() {
+        let mobile = vec![
+            [1.0, 2.0, 3.0],
+            [-2.0, 0.5, 1.0],
+            [0.0, -1.0, 4.0],
+            [3.0, 3.0, -2.0],
+        ];
+        let target: Vec<[f64; 3]> = mobile
+            .iter()
+            .map(|p| {
+                let p = rotate_z(*p, 2.5);
+                [p[0] + 5.0, p[1] - 1.0, p[2] + 0.5]
+            })
+            .collect();
+        let fit = superpose(&mobile, &target, None).unwrap();
+
+        assert!(fit.rmsd < 1e-6);
+        for (p, q) in mobile.iter().zip(&target) {
+            let moved = fit.apply(*p);
+            assert!(rmsd(&[moved], &[*q], None).unwrap() < 1e-6);
+        }
+        assert!((fit.rotation[2][2] - 1.0).abs() < 1e-6);
+        assert_eq!(None, superpose(&mobile, &target[..2], None));
+    }
+
+    #[test]
+    fn it_compute_rmsd_of_noisy_fit() {
+        let mobile = vec![
+            [1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, -1.0, 0.0],
+        ];
+        let target = vec![
+            [1.1, 0.0, 0.0],
+            [-1.1, 0.0, 0.0],
+            [0.0, 0.9, 0.0],
+            [0.0, -0.9, 0.0],
+        ];
+        let fit = superpose(&mobile, &target, None).unwrap();
+
+        let moved: Vec<[f64; 3]> = mobile.iter().map(|p| fit.apply(*p)).collect();
+        assert!((fit.rmsd - 0.1).abs() < 1e-6);
+        assert!((rmsd(&moved, &target, None).unwrap() - fit.rmsd).abs() < 1e-6);
+        assert_eq!(None, rmsd(&moved, &target, Some(&[1.0, 1.0, 1.0])));
+        assert_eq!(None, rmsd(&moved, &target, Some(&[1.0; 5])));
+
+        // all pairs deviate equally, so the weights do not change the RMSD
+        let weights = [10.0, 10.0, 1.0, 1.0];
+        let weighted = superpose(&mobile, &target, Some(&weights)).unwrap();
+        assert!((weighted.rmsd - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn it_reject_non_finite_input() {
+        let mobile = vec![[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let mut target = mobile.clone();
+        target[1][2] = f64::NAN;
+        assert_eq!(None, superpose(&mobile, &target, None));
+        assert_eq!(None, superpose(&target, &mobile, None));
+        assert_eq!(
+            None,
+            superpose(&mobile, &mobile, Some(&[1.0, f64::INFINITY, 1.0]))
+        );
+
+        let mut mmtf = load_173d();
+        mmtf.x_coord_list[5] = f32::NAN;
+        let pairs = [(0, 0), (5, 5), (40, 40)];
+        assert_eq!(None, mmtf.superpose_onto(&mmtf, &pairs, Weighting::Uniform));
+        // picking the largest eigenvalue does not panic on NaN
+        assert!(largest_eigenvector([[f64::NAN; 4]; 4]).0.is_nan());
+    }
+
+    #[test]
+    fn it_superpose_chains() {
+        let mut mmtf = load_173d();
+        let groups = mmtf
+            .chain(0)
+            .align_observed(&mmtf.chain(1), &Default::default());
+        let pairs = mmtf.pair_group_atoms(&mmtf, &groups, &["P", "C1'"]);
+        assert_eq!(15, pairs.len());
+
+        let fit = mmtf
+            .superpose_onto(&mmtf, &pairs, Weighting::Uniform)
+            .unwrap();
+        assert!(fit.rmsd > 0.1 && fit.rmsd < 2.0);
+        // the uniform fit minimizes the unweighted RMSD
+        let by_mass = mmtf.superpose_onto(&mmtf, &pairs, Weighting::Mass).unwrap();
+        let mobile: Vec<[f64; 3]> = pairs
+            .iter()
+            .map(|&(a, _)| to_f64(mmtf.position(a)))
+            .collect();
+        let fixed: Vec<[f64; 3]> = pairs
+            .iter()
+            .map(|&(_, b)| to_f64(mmtf.position(b)))
+            .collect();
+        let unweighted = |fit: &Superposition| {
+            let moved: Vec<[f64; 3]> = mobile.iter().map(|p| fit.apply(*p)).collect();
+            rmsd(&moved, &fixed, None).unwrap()
+        };
+        assert!(unweighted(&by_mass) >= unweighted(&fit) - 1e-6);
+        assert!(mmtf
+            .superpose_onto(&mmtf, &pairs, Weighting::BFactor)
+            .is_some());
+
+        let identity = mmtf.superpose_onto(&mmtf, &[(0, 0), (5, 5), (40, 40)], Weighting::Uniform);
+        assert!(identity.unwrap().rmsd < 1e-3);
+
+        let original = mmtf.clone();
+        fit.apply_to(&mut mmtf);
+        let moved: Vec<[f64; 3]> = pairs
+            .iter()
+            .map(|&(a, _)| to_f64(mmtf.position(a)))
+            .collect();
+        let target: Vec<[f64; 3]> = pairs
+            .iter()
+            .map(|&(_, b)| to_f64(original.position(b)))
+            .collect();
+        assert!((rmsd(&moved, &target, None).unwrap() - fit.rmsd).abs() < 1e-3);
+    }
+
+    #[test]
+    fn it_pair_selected_atoms() {
+        let mmtf = load_173d();
+        let a = mmtf.select_atoms("chain A and name P").unwrap();
+        let b = mmtf.select_atoms("chain B and name P").unwrap();
+        let pairs = pair_atoms(&a, &b).unwrap();
+        assert_eq!(a.len(), pairs.len());
+        assert_eq!(None, pair_atoms(&a, &mmtf.select_atoms("chain B").unwrap()));
+    }
+}
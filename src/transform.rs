@@ -0,0 +1,448 @@
+use mmtf::Mmtf;
+use selection::AtomSet;
+use superposition::to_f64;
+
+/// Affine transformation of coordinates as a *4x4* matrix in row major order
+///
+/// # Examples
+///
+/// ```
+/// use mmtf::transform::Matrix4;
+///
+/// let turn = Matrix4::rotation_about_axis([0.0, 0.0, 1.0], 90.0);
+/// let moved = turn.then(&Matrix4::translation([1.0, 0.0, 0.0]));
+///
+/// let point = moved.apply([1.0, 0.0, 0.0]);
+/// assert!((point[0] - 1.0).abs() < 1e-9 && (point[1] - 1.0).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix4 {
+    /// Rows of the matrix, the translation is the last column
+    pub rows: [[f64; 4]; 4],
+}
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Matrix4::identity()
+    }
+}
+
+impl Matrix4 {
+    /// Transformation leaving all points in place
+    pub fn identity() -> Self {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        Matrix4 { rows }
+    }
+
+    /// Translation by the given offset
+    pub fn translation(offset: [f64; 3]) -> Self {
+        let mut matrix = Matrix4::identity();
+        for (row, value) in matrix.rows.iter_mut().zip(&offset) {
+            row[3] = *value;
+        }
+        matrix
+    }
+
+    /// Rotation (or any linear map) given as a *3x3* matrix about the origin
+    pub fn rotation(rotation: [[f64; 3]; 3]) -> Self {
+        let mut matrix = Matrix4::identity();
+        for (row, linear) in matrix.rows.iter_mut().zip(&rotation) {
+            row[..3].copy_from_slice(linear);
+        }
+        matrix
+    }
+
+    /// Right-handed rotation by `degrees` about the axis through the origin
+    pub fn rotation_about_axis(axis: [f64; 3], degrees: f64) -> Self {
+        let norm = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt();
+        if norm == 0.0 {
+            return Matrix4::identity();
+        }
+        let [x, y, z] = [axis[0] / norm, axis[1] / norm, axis[2] / norm];
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let t = 1.0 - cos;
+        Matrix4::rotation([
+            [t * x * x + cos, t * x * y - sin * z, t * x * z + sin * y],
+            [t * x * y + sin * z, t * y * y + cos, t * y * z - sin * x],
+            [t * x * z - sin * y, t * y * z + sin * x, t * z * z + cos],
+        ])
+    }
+
+    /// Rotation about an axis through the given center
+    pub fn rotation_about(center: [f64; 3], axis: [f64; 3], degrees: f64) -> Self {
+        Matrix4::translation([-center[0], -center[1], -center[2]])
+            .then(&Matrix4::rotation_about_axis(axis, degrees))
+            .then(&Matrix4::translation(center))
+    }
+
+    /// Matrix of 16 values in row major order, like the
+    /// [`Mmtf.ncs_operator_list`](../mmtf/struct.Mmtf.html#structfield.ncs_operator_list)
+    /// and the [`Transform.matrix`](../mmtf/struct.Transform.html#structfield.matrix)
+    /// of bio assemblies
+    pub fn from_row_major(values: &[f32; 16]) -> Self {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = f64::from(values[4 * i + j]);
+            }
+        }
+        Matrix4 { rows }
+    }
+
+    /// Matrix of 16 values in column major order, `None` unless exactly 16
+    /// values are given
+    pub fn from_column_major(values: &[f32]) -> Option<Self> {
+        if values.len() != 16 {
+            return None;
+        }
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = f64::from(values[4 * j + i]);
+            }
+        }
+        Some(Matrix4 { rows })
+    }
+
+    /// The 16 values in row major order
+    pub fn to_row_major(&self) -> [f32; 16] {
+        let mut values = [0.0; 16];
+        for (i, row) in self.rows.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                values[4 * i + j] = *value as f32;
+            }
+        }
+        values
+    }
+
+    /// The 16 values in column major order
+    pub fn to_column_major(&self) -> Vec<f32> {
+        let mut values = vec![0.0; 16];
+        for (i, row) in self.rows.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                values[4 * j + i] = *value as f32;
+            }
+        }
+        values
+    }
+
+    /// Transformation applying this one first and `next` afterwards
+    pub fn then(&self, next: &Matrix4) -> Matrix4 {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| next.rows[i][k] * self.rows[k][j]).sum();
+            }
+        }
+        Matrix4 { rows }
+    }
+
+    /// Determinant of the linear part, the volume scaling of the transformation
+    pub fn determinant(&self) -> f64 {
+        let m = &self.rows;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Whether the linear part is the identity, so that the transformation
+    /// only moves points by a constant offset
+    pub fn is_translation(&self) -> bool {
+        let identity = Matrix4::identity();
+        self.rows
+            .iter()
+            .zip(&identity.rows)
+            .all(|(row, expected)| row[..3] == expected[..3])
+    }
+
+    /// Inverse transformation of an affine matrix, `None` if it is singular
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let det = self.determinant();
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let m = &self.rows;
+        let mut linear = [[0.0; 3]; 3];
+        for (i, row) in linear.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                // transposed cofactors
+                let (r1, r2) = ((j + 1) % 3, (j + 2) % 3);
+                let (c1, c2) = ((i + 1) % 3, (i + 2) % 3);
+                *value = (m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1]) / det;
+            }
+        }
+        let mut inverse = Matrix4::rotation(linear);
+        for (row, linear_row) in inverse.rows.iter_mut().zip(&linear) {
+            row[3] = -(0..3).map(|k| linear_row[k] * m[k][3]).sum::<f64>();
+        }
+        Some(inverse)
+    }
+
+    /// Transform a single point
+    pub fn apply(&self, point: [f64; 3]) -> [f64; 3] {
+        let mut result = [0.0; 3];
+        for (value, row) in result.iter_mut().zip(&self.rows) {
+            *value = row[0] * point[0] + row[1] * point[1] + row[2] * point[2] + row[3];
+        }
+        result
+    }
+}
+
+impl Mmtf {
+    /// Transform the coordinates of all atoms in place
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use std::env;
+    /// use std::fs::File;
+    /// use mmtf::Mmtf;
+    /// use mmtf::transform::Matrix4;
+    ///
+    /// # let file_path = Path::new(&env::current_dir().unwrap())
+    /// #                                    .join("tests")
+    /// #                                    .join("data")
+    /// #                                    .join("173D.mmtf");
+    /// let mut mmtf = Mmtf::from(File::open(&file_path).unwrap()).unwrap();
+    /// let x = mmtf.x_coord_list[0];
+    ///
+    /// mmtf.transform(&Matrix4::translation([1.0, 0.0, 0.0]));
+    /// assert_eq!(x + 1.0, mmtf.x_coord_list[0]);
+    /// ```
+    pub fn transform(&mut self, matrix: &Matrix4) {
+        let m = &matrix.rows;
+        let coordinates = self
+            .x_coord_list
+            .iter_mut()
+            .zip(self.y_coord_list.iter_mut())
+            .zip(self.z_coord_list.iter_mut());
+        for ((x, y), z) in coordinates {
+            let (px, py, pz) = (f64::from(*x), f64::from(*y), f64::from(*z));
+            *x = (m[0][0] * px + m[0][1] * py + m[0][2] * pz + m[0][3]) as f32;
+            *y = (m[1][0] * px + m[1][1] * py + m[1][2] * pz + m[1][3]) as f32;
+            *z = (m[2][0] * px + m[2][1] * py + m[2][2] * pz + m[2][3]) as f32;
+        }
+    }
+
+    /// Transform the coordinates of the selected atoms in place
+    pub fn transform_atoms(&mut self, matrix: &Matrix4, atoms: &AtomSet) {
+        for atom in atoms.iter() {
+            let point = matrix.apply(to_f64(self.position(atom)));
+            self.x_coord_list[atom] = point[0] as f32;
+            self.y_coord_list[atom] = point[1] as f32;
+            self.z_coord_list[atom] = point[2] as f32;
+        }
+    }
+
+    /// Transform the coordinates of all atoms in place and update the data
+    /// which depends on the coordinate frame.
+    ///
+    /// The [NCS operators](struct.Mmtf.html#structfield.ncs_operator_list)
+    /// and the matrices of the [bio assemblies](struct.Mmtf.html#structfield.bio_assembly_list)
+    /// are conjugated with the transformation, so that they still map the
+    /// moved molecules onto each other. Singular transformations leave no
+    /// valid operators, which are removed. Bio assembly matrices are read in
+    /// row major order, the layout of the files of the RCSB PDB, even though
+    /// the MMTF specification describes column major order.
+    ///
+    /// The [unit cell](struct.Mmtf.html#structfield.unit_cell) has no
+    /// orientation, periodic searches assume its standard orientation. It is
+    /// therefore only kept by pure translations and removed otherwise.
+    pub fn transform_with_symmetry(&mut self, matrix: &Matrix4) {
+        self.transform(matrix);
+        if !matrix.is_translation() {
+            self.unit_cell = None;
+        }
+
+        let inverse = match matrix.inverse() {
+            Some(inverse) => inverse,
+            None => {
+                self.ncs_operator_list = None;
+                if let Some(ref mut assemblies) = self.bio_assembly_list {
+                    for assembly in assemblies.iter_mut() {
+                        assembly.transform_list.clear();
+                    }
+                }
+                return;
+            }
+        };
+        let conjugate = |operator: &Matrix4| inverse.then(operator).then(matrix);
+
+        if let Some(ref mut operators) = self.ncs_operator_list {
+            for operator in operators.iter_mut() {
+                *operator = conjugate(&Matrix4::from_row_major(operator)).to_row_major();
+            }
+        }
+        if let Some(ref mut assemblies) = self.bio_assembly_list {
+            for assembly in assemblies.iter_mut() {
+                for transform in assembly.transform_list.iter_mut() {
+                    if transform.matrix.len() == 16 {
+                        let mut values = [0.0; 16];
+                        values.copy_from_slice(&transform.matrix);
+                        let operator = conjugate(&Matrix4::from_row_major(&values));
+                        transform.matrix = operator.to_row_major().to_vec();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Translate all atoms by the given offset
+    pub fn translate(&mut self, offset: [f64; 3]) {
+        self.transform(&Matrix4::translation(offset));
+    }
+
+    /// Rotate all atoms about the given center
+    pub fn rotate(&mut self, rotation: [[f64; 3]; 3], center: [f64; 3]) {
+        let matrix = Matrix4::translation([-center[0], -center[1], -center[2]])
+            .then(&Matrix4::rotation(rotation))
+            .then(&Matrix4::translation(center));
+        self.transform(&matrix);
+    }
+
+    /// Translate all atoms so that the centroid of the selected atoms is at
+    /// the origin, returning the applied offset, or `None` if no atom is selected
+    pub fn center_on(&mut self, atoms: &AtomSet) -> Option<[f64; 3]> {
+        let centroid = self.centroid(atoms)?;
+        let offset = [-centroid[0], -centroid[1], -centroid[2]];
+        self.translate(offset);
+        Some(offset)
+    }
+
+    /// Translate all atoms so that their centroid is at the origin, see
+    /// [`center_on`](#method.center_on)
+    pub fn center(&mut self) -> Option<[f64; 3]> {
+        let all = AtomSet::all(self.x_coord_list.len());
+        self.center_on(&all)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::load_173d;
+
+    fn assert_close(a: [f64; 3], b: [f64; 3], tolerance: f64) {
+        for k in 0..3 {
+            assert!((a[k] - b[k]).abs() < tolerance, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn it_compose_and_invert() {
+        let matrix = Matrix4::rotation_about([1.0, 2.0, 3.0], [1.0, 1.0, 0.0], 40.0)
+            .then(&Matrix4::translation([0.5, -2.0, 7.0]));
+        let inverse = matrix.inverse().unwrap();
+        let point = [3.0, -1.0, 2.5];
+        assert_close(point, inverse.apply(matrix.apply(point)), 1e-9);
+        assert!((matrix.determinant() - 1.0).abs() < 1e-9);
+
+        // the center of a rotation stays in place
+        let rotation = Matrix4::rotation_about([1.0, 2.0, 3.0], [0.0, 0.0, 1.0], 90.0);
+        assert_close([1.0, 2.0, 3.0], rotation.apply([1.0, 2.0, 3.0]), 1e-9);
+        assert_close([1.0, 3.0, 3.0], rotation.apply([2.0, 2.0, 3.0]), 1e-9);
+
+        let singular = Matrix4::rotation([[1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 1.0]]);
+        assert_eq!(None, singular.inverse());
+    }
+
+    #[test]
+    fn it_convert_matrix_layouts() {
+        let matrix = Matrix4::translation([1.0, 2.0, 3.0]);
+        let row_major = matrix.to_row_major();
+        assert_eq!([1.0, 2.0, 3.0], [row_major[3], row_major[7], row_major[11]]);
+        assert_eq!(matrix, Matrix4::from_row_major(&row_major));
+
+        let column_major = matrix.to_column_major();
+        assert_eq!(&[1.0, 2.0, 3.0], &column_major[12..15]);
+        assert_eq!(Some(matrix), Matrix4::from_column_major(&column_major));
+        assert_eq!(None, Matrix4::from_column_major(&column_major[1..]));
+    }
+
+    #[test]
+    fn it_transform_selected_atoms() {
+        let mut mmtf = load_173d();
+        let original = mmtf.clone();
+        let water = mmtf.select_atoms("water").unwrap();
+
+        mmtf.transform_atoms(&Matrix4::translation([0.0, 0.0, 10.0]), &water);
+        for atom in 0..512 {
+            let dz = mmtf.z_coord_list[atom] - original.z_coord_list[atom];
+            let expected = if water.contains(atom) { 10.0 } else { 0.0 };
+            assert!((dz - expected).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn it_center_and_rotate() {
+        let mut mmtf = load_173d();
+        let all = AtomSet::all(512);
+        let radius = mmtf.radius_of_gyration(&all).unwrap();
+
+        let offset = mmtf.center().unwrap();
+        assert_close([0.0; 3], mmtf.centroid(&all).unwrap(), 1e-4);
+        assert!(offset.iter().any(|value| value.abs() > 1.0));
+
+        let turn = Matrix4::rotation_about_axis([1.0, 2.0, 3.0], 75.0).rows;
+        let rotation = [
+            [turn[0][0], turn[0][1], turn[0][2]],
+            [turn[1][0], turn[1][1], turn[1][2]],
+            [turn[2][0], turn[2][1], turn[2][2]],
+        ];
+        mmtf.rotate(rotation, [0.0; 3]);
+        assert_close([0.0; 3], mmtf.centroid(&all).unwrap(), 1e-4);
+        assert!((mmtf.radius_of_gyration(&all).unwrap() - radius).abs() < 1e-3);
+
+        let empty = AtomSet::empty(512);
+        assert_eq!(None, mmtf.center_on(&empty));
+    }
+
+    #[test]
+    fn it_update_symmetry_operators() {
+        let row_major = |values: &[f32]| {
+            let mut array = [0.0; 16];
+            array.copy_from_slice(values);
+            Matrix4::from_row_major(&array)
+        };
+        let mut mmtf = load_173d();
+        let operator = Matrix4::rotation_about_axis([0.0, 0.0, 1.0], 180.0)
+            .then(&Matrix4::translation([5.0, 0.0, 0.0]));
+        mmtf.ncs_operator_list = Some(vec![operator.to_row_major()]);
+        let assembly = &mmtf.bio_assembly_list.as_ref().unwrap()[0].transform_list[1];
+        let assembly = row_major(&assembly.matrix);
+        assert!(!assembly.is_translation());
+
+        let point = to_f64(mmtf.position(0));
+        let (image, assembly_image) = (operator.apply(point), assembly.apply(point));
+        let matrix = Matrix4::rotation_about_axis([1.0, 0.0, 0.0], 30.0)
+            .then(&Matrix4::translation([1.0, 2.0, 3.0]));
+        mmtf.transform_with_symmetry(&matrix);
+
+        // the updated operators map the moved atom onto its moved images
+        let moved = to_f64(mmtf.position(0));
+        let updated = Matrix4::from_row_major(&mmtf.ncs_operator_list.as_ref().unwrap()[0]);
+        assert_close(matrix.apply(image), updated.apply(moved), 1e-3);
+        let transform = &mmtf.bio_assembly_list.as_ref().unwrap()[0].transform_list[1];
+        let updated = row_major(&transform.matrix);
+        assert_close(matrix.apply(assembly_image), updated.apply(moved), 1e-3);
+
+        // a rotated unit cell is no longer valid
+        assert_eq!(None, mmtf.unit_cell);
+    }
+
+    #[test]
+    fn it_keep_unit_cell_on_translation() {
+        let mut mmtf = load_173d();
+        let unit_cell = mmtf.unit_cell.clone();
+        assert!(unit_cell.is_some());
+
+        mmtf.transform_with_symmetry(&Matrix4::translation([1.0, 2.0, 3.0]));
+        assert_eq!(unit_cell, mmtf.unit_cell);
+        mmtf.transform_with_symmetry(&Matrix4::rotation_about_axis([0.0, 0.0, 1.0], 90.0));
+        assert_eq!(None, mmtf.unit_cell);
+    }
+}